@@ -0,0 +1,75 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compares capturing reports through a [`CaptureHandle`], which configures its
+//! [`EventOptions`] once, with building the options for every capture.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use sentry::{ClientOptions, Envelope, Hub, Transport};
+use sentry_eyre::{CaptureHandle, EventOptions};
+use std::{hint::black_box, sync::Arc};
+
+/// Transport that drops every envelope, so only the capture itself is measured.
+struct NoopTransport;
+
+impl Transport for NoopTransport {
+    fn send_envelope(&self, _: Envelope) {}
+}
+
+fn options() -> EventOptions {
+    EventOptions {
+        default_module: Some("daemon".into()),
+        dist: Some("x86_64-unknown-linux-gnu".into()),
+        env_tags: vec![("pod".into(), "POD_NAME".into())],
+        max_chain_depth: Some(16),
+        ..Default::default()
+    }
+}
+
+fn capture_handle(c: &mut Criterion) {
+    let client = sentry::Client::from(ClientOptions {
+        dsn: Some("https://public@sentry.invalid/1".parse().unwrap()),
+        transport: Some(Arc::new(|_: &ClientOptions| {
+            Arc::new(NoopTransport) as Arc<dyn Transport>
+        })),
+        ..Default::default()
+    });
+
+    let hub = Arc::new(Hub::new(Some(Arc::new(client)), Default::default()));
+    let report = eyre::eyre!("tick failed").wrap_err("scheduler stalled");
+
+    Hub::run(hub.clone(), || {
+        let mut group = c.benchmark_group("capture");
+        group.bench_function("options per call", |b| {
+            b.iter(|| CaptureHandle::new(options()).capture(black_box(&report)))
+        });
+
+        let handle = CaptureHandle::new(options());
+        group.bench_function("capture handle", |b| {
+            b.iter(|| handle.capture(black_box(&report)))
+        });
+
+        group.finish();
+    });
+}
+
+criterion_group!(benches, capture_handle);
+criterion_main!(benches);
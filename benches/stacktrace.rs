@@ -0,0 +1,54 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Compares creating an event from a report whose backtrace `stable-eyre` captured, which
+//! converts the backtrace frame by frame and marks the in-app frames, to only formatting and
+//! re-parsing the backtrace.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use stable_eyre::BacktraceExt;
+use std::hint::black_box;
+
+fn stacktrace(c: &mut Criterion) {
+    stable_eyre::HookBuilder::default()
+        .capture_backtrace_by_default(true)
+        .install()
+        .unwrap();
+
+    let report = eyre::eyre!("tick failed").wrap_err("scheduler stalled");
+    let backtrace = report
+        .backtrace()
+        .expect("stable-eyre should capture a backtrace");
+
+    let mut group = c.benchmark_group("stacktrace");
+    group.bench_function("parsed from Debug output", |b| {
+        b.iter(|| sentry_backtrace::parse_stacktrace(&format!("{:#?}", black_box(backtrace))))
+    });
+
+    group.bench_function("event from report", |b| {
+        b.iter(|| sentry_eyre::event_from_report(black_box(&report)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, stacktrace);
+criterion_main!(benches);
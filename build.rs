@@ -0,0 +1,84 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Collects the versions of the crates in the dependency graph from `Cargo.lock` when the
+//! `modules` feature is enabled, so that they can be attached to events as `event.modules`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_MODULES").is_none() {
+        return;
+    }
+
+    let modules = find_lockfile()
+        .map(|path| {
+            println!("cargo:rerun-if-changed={}", path.display());
+            parse_lockfile(&fs::read_to_string(path).unwrap_or_default())
+        })
+        .unwrap_or_default();
+
+    let entries = modules
+        .iter()
+        .map(|(name, version)| format!("({name:?}, {version:?}),\n"))
+        .collect::<String>();
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("`OUT_DIR` to be set"));
+    fs::write(out.join("modules.rs"), format!("&[\n{entries}]\n")).expect("to write `modules.rs`");
+}
+
+/// Finds the `Cargo.lock` of the workspace that is being built by walking up from `OUT_DIR`
+/// (which lives in the workspace's target directory), falling back to this crate's own.
+fn find_lockfile() -> Option<PathBuf> {
+    let out = PathBuf::from(env::var_os("OUT_DIR")?);
+    let manifest = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR")?);
+
+    out.ancestors()
+        .chain(Some(manifest.as_path()))
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| Path::is_file(path))
+}
+
+/// Parses the `name` and `version` of every `[[package]]` of a `Cargo.lock`.
+fn parse_lockfile(contents: &str) -> Vec<(String, String)> {
+    let mut modules = Vec::new();
+    let mut name = None;
+
+    for line in contents.lines().map(str::trim) {
+        if line == "[[package]]" {
+            name = None;
+        } else if let Some(value) = line.strip_prefix("name = ") {
+            name = Some(value.trim_matches('"').to_owned());
+        } else if let Some(value) = line.strip_prefix("version = ") {
+            if let Some(name) = name.take() {
+                modules.push((name, value.trim_matches('"').to_owned()));
+            }
+        }
+    }
+
+    modules.sort();
+    modules.dedup();
+    modules
+}
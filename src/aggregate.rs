@@ -0,0 +1,276 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    attach_mechanisms, event_from_report, event_from_report_extended, hooks, options,
+    should_capture, unprocessed_event_from_report,
+};
+use eyre::Report;
+use sentry_core::{
+    event_from_error,
+    protocol::{Event, LogEntry},
+    types::Uuid,
+    Hub,
+};
+use std::error::Error;
+
+/// An error that aggregates several independent errors, rather than having a single
+/// [`source`](Error::source). Implement this for such error types to capture each of
+/// the aggregated errors with [`capture_aggregate`].
+pub trait AggregateError {
+    /// Returns the errors that are aggregated by this error.
+    fn sources(&self) -> Vec<&(dyn Error + 'static)>;
+}
+
+/// Captures a [`Report`] whose error aggregates several independent errors, sending one
+/// event per aggregated error. Each event holds the aggregated error's chain as its innermost
+/// exceptions, followed by the report's chain, and is tagged with its index in the aggregate
+/// as `error.aggregate_index`.
+///
+/// If the aggregate doesn't hold any errors, the report is captured as a single event.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_aggregate, AggregateError, EventOptions};
+/// use std::{error::Error, fmt, io};
+///
+/// #[derive(Debug)]
+/// struct BatchError(Vec<io::Error>);
+///
+/// impl fmt::Display for BatchError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "{} jobs failed", self.0.len())
+///     }
+/// }
+///
+/// impl Error for BatchError {}
+///
+/// impl AggregateError for BatchError {
+///     fn sources(&self) -> Vec<&(dyn Error + 'static)> {
+///         self.0.iter().map(|err| err as &(dyn Error + 'static)).collect()
+///     }
+/// }
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::Report::new(BatchError(vec![
+///         io::Error::other("disk full"),
+///         io::Error::other("connection reset"),
+///     ]));
+///
+///     let aggregate = report.downcast_ref::<BatchError>().unwrap();
+///     let options = EventOptions {
+///         record_capture_timing: true,
+///         ..Default::default()
+///     };
+///
+///     sentry_eyre::with_options(options, || {
+///         assert_eq!(capture_aggregate(&report, aggregate).len(), 2);
+///     });
+/// });
+///
+/// assert_eq!(events.len(), 2);
+/// assert_eq!(events[0].exception[0].value.as_deref(), Some("disk full"));
+/// assert_eq!(events[0].exception[1].value.as_deref(), Some("2 jobs failed"));
+/// assert_eq!(events[1].exception[0].value.as_deref(), Some("connection reset"));
+/// assert_eq!(events[1].tags["error.aggregate_index"], "1");
+/// assert!(events[1].extra["capture_build_ms"].is_f64());
+/// ```
+pub fn capture_aggregate<A: AggregateError + ?Sized>(report: &Report, aggregate: &A) -> Vec<Uuid> {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Vec::new();
+        }
+
+        let sources = aggregate.sources();
+        if sources.is_empty() {
+            return vec![hooks::capture_event(hub, report, event_from_report(report))];
+        }
+
+        let options = options::current_options();
+        sources
+            .into_iter()
+            .enumerate()
+            .map(|(idx, source)| {
+                let event = event_from_report_extended(report, &options, |event| {
+                    // Exceptions are ordered from innermost to outermost, so the aggregated
+                    // error's chain goes in front of the report's.
+                    let inner = event_from_error(source).exception.values;
+                    event.exception.values.splice(0..0, inner);
+                    attach_mechanisms(event);
+                    event
+                        .tags
+                        .insert("error.aggregate_index".into(), idx.to_string());
+                });
+
+                hooks::capture_event(hub, report, event)
+            })
+            .collect()
+    })
+}
+
+/// How [`capture_reports`] captures a batch of reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BatchMode {
+    /// Captures a single event that holds the exceptions of all reports, with a summary of
+    /// how many reports failed as its message. The exceptions of each report hold the
+    /// report's index in the batch as `batch_index` in their mechanism's data.
+    Combined,
+
+    /// Captures one event per report within a single pushed scope, each tagged with the
+    /// report's index in the batch as `batch.index`. This is the default.
+    #[default]
+    Separate,
+}
+
+/// Captures a batch of [`Report`]s at once, i.e. the failures of jobs that ran concurrently,
+/// either as one combined event or as separate events depending on the given [`BatchMode`].
+/// Every event is tagged with the number of reports in the batch as `batch.size`.
+///
+/// Reports that aren't captured (i.e. when they're suppressed) are left out, and the ids of the
+/// captured events are returned.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_reports, BatchMode, EventOptions};
+///
+/// let reports = [eyre::eyre!("disk full"), eyre::eyre!("connection reset")];
+/// let events = sentry::test::with_captured_events(|| {
+///     assert_eq!(capture_reports(&reports, BatchMode::Separate).len(), 2);
+///     assert_eq!(capture_reports(&reports, BatchMode::Combined).len(), 1);
+/// });
+///
+/// assert_eq!(events.len(), 3);
+/// assert_eq!(events[1].tags["batch.index"], "1");
+/// assert_eq!(events[1].tags["batch.size"], "2");
+///
+/// let combined = &events[2];
+/// assert_eq!(combined.logentry.as_ref().unwrap().message, "2 reports failed");
+/// assert_eq!(combined.exception[0].value.as_deref(), Some("connection reset"));
+/// assert_eq!(combined.exception[1].value.as_deref(), Some("disk full"));
+/// assert_eq!(
+///     combined.exception[0].mechanism.as_ref().unwrap().data["batch_index"],
+///     1
+/// );
+///
+/// // Options apply to the combined event, rather than to each report's.
+/// let reports = [
+///     eyre::eyre!("disk full"),
+///     eyre::eyre!("connection reset"),
+///     eyre::eyre!("timed out"),
+/// ];
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let options = EventOptions {
+///         max_chain_depth: Some(2),
+///         ..Default::default()
+///     };
+///
+///     sentry_eyre::with_options(options, || capture_reports(&reports, BatchMode::Combined));
+/// });
+///
+/// let types = events[0].exception.iter().map(|exc| exc.ty.as_str()).collect::<Vec<_>>();
+/// assert_eq!(types.iter().filter(|&&ty| ty == "TruncatedChain").count(), 1);
+/// assert_eq!(events[0].exception.len(), 3);
+/// ```
+pub fn capture_reports<'a, I>(reports: I, mode: BatchMode) -> Vec<Uuid>
+where
+    I: IntoIterator<Item = &'a Report>,
+{
+    Hub::with_active(|hub| {
+        let reports = reports.into_iter().collect::<Vec<_>>();
+        let size = reports.len();
+
+        // Checking whether to capture a report needs to happen once per report, since it
+        // might be sampled.
+        let reports = reports
+            .into_iter()
+            .enumerate()
+            .filter(|(_, report)| should_capture(hub, report))
+            .collect::<Vec<_>>();
+
+        match mode {
+            BatchMode::Combined => {
+                let Some(&(_, first)) = reports.first() else {
+                    return Vec::new();
+                };
+
+                let mut event = combine_events(&reports, size);
+                event.tags.insert("batch.size".into(), size.to_string());
+                vec![hooks::capture_event(hub, first, event)]
+            }
+
+            BatchMode::Separate => {
+                let _guard = hub.push_scope();
+                hub.configure_scope(|scope| scope.set_tag("batch.size", size.to_string()));
+
+                reports
+                    .into_iter()
+                    .map(|(idx, report)| {
+                        let mut event = event_from_report(report);
+                        event.tags.insert("batch.index".into(), idx.to_string());
+                        hooks::capture_event(hub, report, event)
+                    })
+                    .collect()
+            }
+        }
+    })
+}
+
+/// Combines the events of the given reports (along with their index in the batch of the given
+/// size) into one, based on the first report's event. Exceptions are ordered from innermost to
+/// outermost, so the exceptions of the first report go last. The
+/// [`EventOptions`](crate::EventOptions) are applied once, to the combined event.
+fn combine_events(reports: &[(usize, &Report)], size: usize) -> Event<'static> {
+    let options = options::current_options();
+    let (first_idx, first) = reports[0];
+
+    event_from_report_extended(first, &options, |event| {
+        tag_batch_index(event, first_idx);
+
+        let exceptions = reports[1..]
+            .iter()
+            .rev()
+            .flat_map(|&(idx, report)| {
+                let mut event = unprocessed_event_from_report(report, &options);
+                tag_batch_index(&mut event, idx);
+                event.exception.values
+            })
+            .collect::<Vec<_>>();
+
+        event.exception.values.splice(0..0, exceptions);
+        event.logentry = Some(LogEntry {
+            message: format!("{size} reports failed"),
+            params: Vec::new(),
+        });
+    })
+}
+
+/// Records the report's index in the batch as `batch_index` in the mechanism's data of every
+/// exception of its event.
+fn tag_batch_index(event: &mut Event<'static>, idx: usize) {
+    for mechanism in event
+        .exception
+        .iter_mut()
+        .filter_map(|exc| exc.mechanism.as_mut())
+    {
+        mechanism.data.insert("batch_index".into(), idx.into());
+    }
+}
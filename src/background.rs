@@ -0,0 +1,180 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    event_from_report_with_options,
+    hooks::{self, PreparedEvent},
+    should_capture, EventOptions,
+};
+use eyre::Report;
+use sentry_core::{types::Uuid, Client, Hub, Scope};
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Captures reports on a dedicated worker thread, so that sending events doesn't add latency
+/// to the calling thread.
+///
+/// The calling thread builds the event just like [`capture_report`](crate::capture_report)
+/// does (with the given [`EventOptions`]) and runs the [`before_capture`](crate::add_before_capture)
+/// hooks, since both need the report itself. It then queues the event on a bounded channel
+/// along with a snapshot of its current scope and client; the worker applies that scope (its
+/// tags, breadcrumbs and event processors), runs `before_send` and sends the event. When the
+/// channel is full, reports are dropped and counted (see [`BackgroundCapturer::dropped`]).
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{push_context, BackgroundCapturer, EventOptions};
+///
+/// let mut dropped = 0;
+/// let events = sentry::test::with_captured_events(|| {
+///     let capturer = BackgroundCapturer::with_capacity(EventOptions::default(), 4).unwrap();
+///     push_context("job.queue", "emails");
+///     for idx in 0..100 {
+///         sentry::with_scope(
+///             |scope| scope.set_tag("job.id", idx),
+///             || capturer.capture(&eyre::eyre!("job {idx} failed")),
+///         );
+///     }
+///
+///     dropped = capturer.dropped();
+///     capturer.shutdown();
+/// });
+///
+/// assert_eq!(events.len() as u64 + dropped, 100);
+/// assert!(events.iter().all(|event| event.tags["job.queue"] == "emails"));
+/// assert!(events.iter().all(|event| {
+///     let message = &event.exception[0].value.as_deref().unwrap();
+///     message == &format!("job {} failed", event.tags["job.id"])
+/// }));
+/// ```
+pub struct BackgroundCapturer {
+    sender: Option<SyncSender<QueuedEvent>>,
+    worker: Option<JoinHandle<()>>,
+    options: Arc<EventOptions>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// An event that was queued on the calling thread, along with its client and scope.
+struct QueuedEvent {
+    prepared: PreparedEvent,
+    client: Option<Arc<Client>>,
+    scope: Scope,
+}
+
+impl BackgroundCapturer {
+    /// The default capacity of the channel to the worker thread.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Spawns the worker thread; events are built with the given [`EventOptions`]. Returns an
+    /// error if the thread couldn't be spawned.
+    pub fn spawn(options: EventOptions) -> io::Result<Self> {
+        Self::with_capacity(options, Self::DEFAULT_CAPACITY)
+    }
+
+    /// Same as [`BackgroundCapturer::spawn`], but with a custom capacity of the channel to
+    /// the worker thread.
+    pub fn with_capacity(options: EventOptions, capacity: usize) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel::<QueuedEvent>(capacity);
+        let worker = thread::Builder::new()
+            .name("sentry-eyre".into())
+            .spawn(move || {
+                for queued in receiver {
+                    let hub = Hub::new(queued.client, Arc::new(queued.scope));
+                    queued.prepared.capture(&hub);
+                }
+            })?;
+
+        Ok(BackgroundCapturer {
+            sender: Some(sender),
+            worker: Some(worker),
+            options: Arc::new(options),
+            dropped: Arc::default(),
+        })
+    }
+
+    /// Queues the given [`Report`] to be captured on the worker thread, returning the id
+    /// the event will have, or [`None`] if the report wasn't queued (i.e. because the channel
+    /// is full, no client is bound, or a hook dropped the event).
+    pub fn capture(&self, report: &Report) -> Option<Uuid> {
+        let sender = self.sender.as_ref()?;
+        let queued = Hub::with_active(|hub| {
+            if !should_capture(hub, report) {
+                return None;
+            }
+
+            let event = event_from_report_with_options(report, &self.options);
+            Some(QueuedEvent {
+                prepared: hooks::prepare_event(
+                    hub,
+                    report,
+                    event,
+                    false,
+                    self.options.max_payload_bytes,
+                )?,
+                client: hub.client(),
+                scope: hub.configure_scope(|scope| scope.clone()),
+            })
+        })?;
+
+        let event_id = queued.prepared.event_id();
+        match sender.try_send(queued) {
+            Ok(()) => Some(event_id),
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+
+            // The worker only stops once the sender is dropped, unless it panicked.
+            Err(TrySendError::Disconnected(_)) => None,
+        }
+    }
+
+    /// Returns how many reports were dropped because the channel was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Stops accepting reports and waits until the worker thread captured all queued ones.
+    /// Dropping the capturer does the same.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        drop(self.sender.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for BackgroundCapturer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,266 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Backtrace extraction from a [`Report`].
+//!
+//! Backtraces are extracted by one of the backends that are enabled via crate features. Since
+//! Cargo features are additive, more than one backend can end up being enabled (i.e. with
+//! `--all-features`); in that case, the backends are tried in the following order and the
+//! first backtrace that could be extracted wins:
+//!
+//! 1. `stable-backtrace` — the backtrace captured by [`stable_eyre`]'s handler.
+//! 2. `color-eyre` — the backtrace captured by [`color_eyre`]'s handler.
+//! 3. `std-backtrace` — the [`std::backtrace::Backtrace`] captured by `eyre`'s own
+//!    [`DefaultHandler`](eyre::DefaultHandler), if it wasn't replaced by another handler.
+//!
+//! The backtraces of `stable-eyre` and `color-eyre` are converted into a stacktrace frame by
+//! frame, while the one of `eyre`'s default handler can only be parsed from its formatted
+//! output. Either way, every frame is marked as in-app or not: the client's
+//! `in_app_include`/`in_app_exclude` prefixes win, and frames that match neither are in-app
+//! unless they're from the standard library, `eyre` or this crate.
+//! [`EventOptions::in_app_fn`](crate::EventOptions::in_app_fn) overrides all of that.
+//!
+//! When no backend is enabled, no stacktrace is attached to events, except for the reports of
+//! `test::report_with_backtrace` (with `test-util`), which capture their own backtrace.
+//!
+//! A backtrace that was extracted but couldn't be parsed (i.e. because its format changed) is
+//! attached as-is as the event's `raw_backtrace` extra, so the information isn't lost.
+
+use crate::fingerprint::is_probably_in_app;
+use eyre::Report;
+use sentry_core::{protocol::Stacktrace, Hub};
+
+/// A backtrace that was extracted from a [`Report`].
+#[cfg_attr(
+    not(any(
+        feature = "stable-backtrace",
+        feature = "color-eyre",
+        feature = "std-backtrace",
+        feature = "test-util"
+    )),
+    allow(dead_code)
+)]
+pub(crate) enum ReportBacktrace {
+    /// The backtrace was parsed into a [`Stacktrace`].
+    Parsed(Stacktrace),
+
+    /// The backtrace couldn't be parsed (i.e. because its format changed), so it's kept
+    /// as-is to not lose the information.
+    Raw(String),
+}
+
+/// Extracts a [`Stacktrace`] from the given [`Report`] with the enabled backtrace backends.
+pub(crate) fn stacktrace_from_report(report: &Report) -> Option<Stacktrace> {
+    match backtrace_from_report(report)? {
+        ReportBacktrace::Parsed(stacktrace) => Some(stacktrace),
+        ReportBacktrace::Raw(_) => None,
+    }
+}
+
+/// Extracts the backtrace from the given [`Report`] with the enabled backtrace backends,
+/// falling back to the raw backtrace if it couldn't be parsed.
+pub(crate) fn backtrace_from_report(report: &Report) -> Option<ReportBacktrace> {
+    let mut backtrace = converted_backtrace_from_report(report)
+        .map(ReportBacktrace::Parsed)
+        .or_else(|| raw_backtrace_from_report(report).and_then(parse_backtrace));
+
+    if let Some(ReportBacktrace::Parsed(stacktrace)) = &mut backtrace {
+        mark_in_app(stacktrace);
+    }
+
+    backtrace
+}
+
+/// Converts the backtraces of `stable-eyre` and `color-eyre` frame by frame, which is a lot
+/// cheaper than formatting them and parsing the output again.
+fn converted_backtrace_from_report(report: &Report) -> Option<Stacktrace> {
+    #[cfg(feature = "stable-backtrace")]
+    if let Some(stacktrace) = stable::stacktrace_from_report(report) {
+        return Some(stacktrace);
+    }
+
+    #[cfg(feature = "color-eyre")]
+    if let Some(stacktrace) = color::stacktrace_from_report(report) {
+        return Some(stacktrace);
+    }
+
+    #[cfg(not(any(feature = "stable-backtrace", feature = "color-eyre")))]
+    let _ = report;
+
+    None
+}
+
+/// Marks every frame that isn't marked yet as in-app or not, with the `in_app_include` and
+/// `in_app_exclude` prefixes of the current client first and [`is_probably_in_app`] otherwise.
+fn mark_in_app(stacktrace: &mut Stacktrace) {
+    let client = Hub::with(|hub| hub.client());
+    let (include, exclude) = client
+        .as_ref()
+        .map(|client| {
+            let options = client.options();
+            (&options.in_app_include[..], &options.in_app_exclude[..])
+        })
+        .unwrap_or_default();
+
+    for frame in stacktrace
+        .frames
+        .iter_mut()
+        .filter(|frame| frame.in_app.is_none())
+    {
+        let function = frame.function.as_deref().unwrap_or_default();
+        frame.in_app = Some(
+            if include.iter().any(|prefix| function.starts_with(prefix)) {
+                true
+            } else if exclude.iter().any(|prefix| function.starts_with(prefix)) {
+                false
+            } else {
+                is_probably_in_app(frame)
+            },
+        );
+    }
+}
+
+/// Extracts the formatted, but not yet parsed backtrace from the given [`Report`] with the
+/// enabled backtrace backends.
+pub(crate) fn raw_backtrace_from_report(report: &Report) -> Option<String> {
+    #[cfg(feature = "test-util")]
+    if let Some(err) = report.downcast_ref::<crate::test::BacktraceError>() {
+        return Some(err.backtrace().to_string());
+    }
+
+    #[cfg(feature = "stable-backtrace")]
+    if let Some(backtrace) = stable::raw_backtrace_from_report(report) {
+        return Some(backtrace);
+    }
+
+    #[cfg(feature = "color-eyre")]
+    if let Some(backtrace) = color::raw_backtrace_from_report(report) {
+        return Some(backtrace);
+    }
+
+    #[cfg(feature = "std-backtrace")]
+    if let Some(backtrace) = default::raw_backtrace_from_report(report) {
+        return Some(backtrace);
+    }
+
+    #[cfg(not(any(
+        feature = "stable-backtrace",
+        feature = "color-eyre",
+        feature = "std-backtrace",
+        feature = "test-util"
+    )))]
+    let _ = report;
+
+    None
+}
+
+/// Parses a formatted backtrace, keeping it as-is if it isn't empty but can't be parsed.
+pub(crate) fn parse_backtrace(backtrace: String) -> Option<ReportBacktrace> {
+    #[cfg(any(
+        feature = "stable-backtrace",
+        feature = "color-eyre",
+        feature = "std-backtrace",
+        feature = "test-util"
+    ))]
+    return match sentry_backtrace::parse_stacktrace(&backtrace) {
+        Some(stacktrace) => Some(ReportBacktrace::Parsed(stacktrace)),
+        None if backtrace.trim().is_empty() => None,
+        None => Some(ReportBacktrace::Raw(backtrace)),
+    };
+
+    #[cfg(not(any(
+        feature = "stable-backtrace",
+        feature = "color-eyre",
+        feature = "std-backtrace",
+        feature = "test-util"
+    )))]
+    {
+        let _ = backtrace;
+        None
+    }
+}
+
+#[cfg(feature = "stable-backtrace")]
+mod stable {
+    use eyre::Report;
+    use sentry_core::protocol::Stacktrace;
+    use stable_eyre::BacktraceExt;
+
+    pub(super) fn stacktrace_from_report(report: &Report) -> Option<Stacktrace> {
+        sentry_backtrace::backtrace_to_stacktrace(report.backtrace()?)
+    }
+
+    pub(super) fn raw_backtrace_from_report(report: &Report) -> Option<String> {
+        report
+            .backtrace()
+            .map(|backtrace| format!("{backtrace:#?}"))
+    }
+}
+
+#[cfg(feature = "color-eyre")]
+mod color {
+    use eyre::Report;
+    use sentry_core::protocol::Stacktrace;
+
+    pub(super) fn stacktrace_from_report(report: &Report) -> Option<Stacktrace> {
+        let handler = report.handler().downcast_ref::<color_eyre::Handler>()?;
+        sentry_backtrace::backtrace_to_stacktrace(handler.backtrace()?)
+    }
+
+    pub(super) fn raw_backtrace_from_report(report: &Report) -> Option<String> {
+        report
+            .handler()
+            .downcast_ref::<color_eyre::Handler>()?
+            .backtrace()
+            .map(|backtrace| format!("{backtrace:?}"))
+    }
+}
+
+#[cfg(feature = "std-backtrace")]
+mod default {
+    use eyre::{DefaultHandler, Report};
+
+    /// The header of the section that [`DefaultHandler`] renders the backtrace in.
+    const BACKTRACE_HEADER: &str = "\n\nStack backtrace:\n";
+
+    pub(super) fn raw_backtrace_from_report(report: &Report) -> Option<String> {
+        let handler = report.handler();
+
+        #[cfg(not(feature = "stable-backtrace"))]
+        let is_default =
+            handler.is::<DefaultHandler>() || handler.is::<crate::install::InstalledHandler>();
+
+        #[cfg(feature = "stable-backtrace")]
+        let is_default = handler.is::<DefaultHandler>();
+
+        if !is_default {
+            return None;
+        }
+
+        // The handler doesn't expose its backtrace, but renders it as the last section of the
+        // report's `Debug` output if it was captured (i.e. with `RUST_BACKTRACE=1`, or by the
+        // handler that `Installer` installs).
+        let debug = format!("{report:?}");
+        let (_, backtrace) = debug.rsplit_once(BACKTRACE_HEADER)?;
+
+        Some(backtrace.to_owned())
+    }
+}
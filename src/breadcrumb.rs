@@ -0,0 +1,102 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use eyre::Report;
+use sentry_core::{
+    parse_type_from_debug,
+    protocol::{Breadcrumb, Level, Map, Value},
+    Hub,
+};
+use std::error::Error;
+
+/// Creates a [`Breadcrumb`] that records the given [`Report`] rather than capturing it as
+/// its own event, i.e. for a failed step of a workflow that is summarized by a later event.
+/// The breadcrumb has the [`Level::Error`] level, the `eyre` category, the report's message,
+/// the type of its error as the `type` data, and the messages of all links of its chain as
+/// the `chain` data.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::breadcrumb_from_report;
+/// use std::io;
+///
+/// let report = eyre::Report::new(io::Error::new(io::ErrorKind::Other, "disk full"));
+/// let breadcrumb = breadcrumb_from_report(&report);
+///
+/// assert_eq!(breadcrumb.ty, "error");
+/// assert_eq!(breadcrumb.level, Level::Error);
+/// assert_eq!(breadcrumb.message.as_deref(), Some("disk full"));
+/// assert_eq!(breadcrumb.category.as_deref(), Some("eyre"));
+/// assert_eq!(breadcrumb.data["type"], "Custom");
+/// assert_eq!(breadcrumb.data["chain"], serde_json::json!(["disk full"]));
+///
+/// sentry::add_breadcrumb(breadcrumb);
+/// ```
+pub fn breadcrumb_from_report(report: &Report) -> Breadcrumb {
+    breadcrumb_from_error(report.as_ref())
+}
+
+/// Adds a breadcrumb for the given [`Report`] (see [`breadcrumb_from_report`]) to the current
+/// [`Hub`], so that a recoverable error shows up as context on the next event.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{add_report_breadcrumb, capture_report};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     add_report_breadcrumb(&eyre::eyre!("cache miss"));
+///     capture_report(&eyre::eyre!("request failed"));
+/// });
+///
+/// assert_eq!(events[0].breadcrumbs[0].message.as_deref(), Some("cache miss"));
+/// ```
+pub fn add_report_breadcrumb(report: &Report) {
+    Hub::with_active(|hub| hub.add_breadcrumb(breadcrumb_from_report(report)));
+}
+
+/// Same as [`breadcrumb_from_report`], but for the error of a report (i.e. in an eyre hook,
+/// where the report doesn't exist yet).
+#[cfg_attr(
+    not(any(feature = "stable-backtrace", feature = "std-backtrace")),
+    allow(dead_code)
+)]
+pub(crate) fn breadcrumb_from_error(err: &dyn Error) -> Breadcrumb {
+    let debug = format!("{err:?}");
+
+    let mut data = Map::new();
+    data.insert("type".into(), parse_type_from_debug(&debug).into());
+
+    let chain = std::iter::successors(Some(err), |&err| err.source())
+        .map(|err| Value::from(err.to_string()))
+        .collect();
+
+    data.insert("chain".into(), Value::Array(chain));
+
+    Breadcrumb {
+        ty: "error".into(),
+        category: Some("eyre".into()),
+        level: Level::Error,
+        message: Some(err.to_string()),
+        data,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,490 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    event_from_report_with_options, hooks, options::current_options, should_capture, EventOptions,
+    Fingerprint,
+};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Event, Level, LogEntry, Map, Mechanism, Request, Thread, ThreadId, Value},
+    types::Uuid,
+    Hub,
+};
+use std::{borrow::Cow, collections::HashMap};
+
+/// Builder to customize how a [`Report`] is represented as a Sentry event before
+/// it gets captured.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::ThreadId;
+/// use sentry_eyre::CaptureReportBuilder;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::eyre!("worker failed");
+///     CaptureReportBuilder::new(&report).thread_id("worker-3").capture();
+/// });
+///
+/// let exc = events[0].exception.last().unwrap();
+/// assert_eq!(exc.thread_id, Some(ThreadId::String("worker-3".into())));
+/// ```
+pub struct CaptureReportBuilder<'a> {
+    report: &'a Report,
+    options: EventOptions,
+    level: Option<Level>,
+    fingerprint: Option<Fingerprint>,
+    tags: Map<String, String>,
+    extra: Map<String, Value>,
+    exception_data: Vec<(usize, HashMap<String, Value>)>,
+    thread_id: Option<String>,
+    request: Option<Request>,
+    log_entry: Option<LogEntry>,
+    attach_debug_report: bool,
+    on_captured: Option<Box<dyn FnOnce(Uuid) + 'a>>,
+}
+
+impl<'a> CaptureReportBuilder<'a> {
+    /// Creates a new [`CaptureReportBuilder`] for the given [`Report`].
+    pub fn new(report: &'a Report) -> Self {
+        CaptureReportBuilder {
+            report,
+            options: EventOptions::clone(&current_options()),
+            level: None,
+            fingerprint: None,
+            tags: Map::new(),
+            extra: Map::new(),
+            exception_data: Vec::new(),
+            thread_id: None,
+            request: None,
+            log_entry: None,
+            attach_debug_report: false,
+            on_captured: None,
+        }
+    }
+
+    /// Uses the given [`EventOptions`] when creating the event, instead of the effective
+    /// [default options](crate::set_default_options).
+    pub fn options(mut self, options: EventOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Sets the event's `dist`, overriding [`EventOptions::dist`].
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("render failed");
+    ///     CaptureReportBuilder::new(&report).dist("win-x64").capture();
+    /// });
+    ///
+    /// assert_eq!(events[0].dist.as_deref(), Some("win-x64"));
+    /// ```
+    pub fn dist<D: Into<Cow<'static, str>>>(mut self, dist: D) -> Self {
+        self.options.dist = Some(dist.into());
+        self
+    }
+
+    /// Sets the event's `server_name`, overriding [`EventOptions::server_name`].
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let report = eyre::eyre!("disk full");
+    /// let event = CaptureReportBuilder::new(&report)
+    ///     .server_name("worker-eu-3")
+    ///     .build();
+    ///
+    /// assert_eq!(event.server_name.as_deref(), Some("worker-eu-3"));
+    /// ```
+    pub fn server_name<S: Into<Cow<'static, str>>>(mut self, server_name: S) -> Self {
+        self.options.server_name = Some(server_name.into());
+        self
+    }
+
+    /// Sets the [`Level`] of the event, which is [`Level::Error`] by default.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Sets the fingerprint of the event, which controls how it is grouped into an issue. The
+    /// special `{{ default }}` component is kept as-is, see [`Fingerprint::with_default`].
+    pub fn fingerprint<F: Into<Fingerprint>>(mut self, fingerprint: F) -> Self {
+        self.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Adds a tag to the event. Tags of the current scope with the same key take precedence.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Level;
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("query timed out");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .level(Level::Warning)
+    ///         .tag("subsystem", "db")
+    ///         .fingerprint(["db-timeout"])
+    ///         .extra("query", "SELECT 1")
+    ///         .capture();
+    /// });
+    ///
+    /// assert_eq!(events[0].level, Level::Warning);
+    /// assert_eq!(events[0].tags["subsystem"], "db");
+    /// assert_eq!(events[0].fingerprint[..], ["db-timeout"]);
+    /// assert_eq!(events[0].extra["query"], "SELECT 1");
+    /// ```
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tags.insert(key.into(), value.into());
+        self
+    }
+
+    /// Adds additional arbitrary data to the event's `extra`.
+    pub fn extra<K: Into<String>, V: Into<Value>>(mut self, key: K, value: V) -> Self {
+        self.extra.insert(key.into(), value.into());
+        self
+    }
+
+    /// Attaches a note from the operator that triggered the error as the `operator_note`
+    /// extra, i.e. to capture human context in internal tools. The note is only stored on the
+    /// event itself; use [`UserFeedback`](crate::UserFeedback) or
+    /// [`send_user_feedback`](crate::send_user_feedback) to submit it as Sentry user feedback.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("migration failed");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .note("ran the migration during the maintenance window")
+    ///         .capture();
+    /// });
+    ///
+    /// assert_eq!(
+    ///     events[0].extra["operator_note"],
+    ///     "ran the migration during the maintenance window"
+    /// );
+    /// ```
+    pub fn note(self, text: &str) -> Self {
+        self.extra("operator_note", text)
+    }
+
+    /// Adds additional arbitrary data to the event's `extra`, where `serializer` controls how
+    /// the value is represented (i.e. to omit sensitive fields).
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Value;
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// struct Account {
+    ///     name: String,
+    ///     password: String,
+    /// }
+    ///
+    /// let account = Account {
+    ///     name: "noel".into(),
+    ///     password: "hunter2".into(),
+    /// };
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("login failed");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .extra_with("account", &account, |account| {
+    ///             Value::from_iter([("name", account.name.clone())])
+    ///         })
+    ///         .capture();
+    /// });
+    ///
+    /// let account = &events[0].extra["account"];
+    /// assert_eq!(account["name"], "noel");
+    /// assert!(account.get("password").is_none());
+    /// ```
+    pub fn extra_with<K, T, F>(self, key: K, value: &T, serializer: F) -> Self
+    where
+        K: Into<String>,
+        F: FnOnce(&T) -> Value,
+    {
+        self.extra(key, serializer(value))
+    }
+
+    /// Attaches free-form data to the exception at the given index, i.e. the value of a
+    /// variable at that layer of the chain. Exceptions are ordered from the innermost cause
+    /// (`0`) to the outermost error; indices that are out of bounds are ignored.
+    ///
+    /// The data is stored in the exception's `mechanism`, next to its `chain_index`.
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry_eyre::CaptureReportBuilder;
+    /// use std::collections::HashMap;
+    ///
+    /// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///     .wrap_err("query failed")
+    ///     .unwrap_err();
+    ///
+    /// let event = CaptureReportBuilder::new(&report)
+    ///     .exception_data(0, HashMap::from([("attempt".into(), 3.into())]))
+    ///     .build();
+    ///
+    /// let mechanism = event.exception[0].mechanism.as_ref().unwrap();
+    /// assert_eq!(mechanism.data["attempt"], 3);
+    /// assert!(!event.exception[1].mechanism.as_ref().unwrap().data.contains_key("attempt"));
+    /// ```
+    pub fn exception_data(mut self, index: usize, data: HashMap<String, Value>) -> Self {
+        self.exception_data.push((index, data));
+        self
+    }
+
+    /// Links all exceptions of the event to a logical thread (like a worker id) rather
+    /// than the OS thread, and adds a matching thread entry to the event.
+    pub fn thread_id<I: Into<String>>(mut self, id: I) -> Self {
+        self.thread_id = Some(id.into());
+        self
+    }
+
+    /// Attaches the given [`Request`] context to the event, i.e. for errors while
+    /// handling HTTP requests.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Request;
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("handler failed");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .request(Request {
+    ///             method: Some("POST".into()),
+    ///             url: "https://example.com/users".parse().ok(),
+    ///             ..Default::default()
+    ///         })
+    ///         .capture();
+    /// });
+    ///
+    /// let request = events[0].request.as_ref().unwrap();
+    /// assert_eq!(request.method.as_deref(), Some("POST"));
+    /// ```
+    pub fn request(mut self, request: Request) -> Self {
+        self.request = Some(request);
+        self
+    }
+
+    /// Sets the event's parameterized message, where `template` uses `%s` as placeholders for
+    /// the `params`. Sentry groups by the template, so this is useful when the report's message
+    /// embeds dynamic values.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let report = eyre::eyre!("user 42 not found");
+    /// let event = CaptureReportBuilder::new(&report)
+    ///     .log_entry("user %s not found", vec!["42".into()])
+    ///     .build();
+    ///
+    /// let logentry = event.logentry.unwrap();
+    /// assert_eq!(logentry.message, "user %s not found");
+    /// assert_eq!(logentry.params, ["42"]);
+    /// ```
+    pub fn log_entry(mut self, template: &str, params: Vec<String>) -> Self {
+        self.log_entry = Some(LogEntry {
+            message: template.to_owned(),
+            params: params.into_iter().map(Value::from).collect(),
+        });
+
+        self
+    }
+
+    /// Runs the given callback with the event's id right after it was captured, i.e. to
+    /// expose the id in a response header. The callback isn't run when the report isn't
+    /// captured at all.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("handler failed");
+    ///     let mut captured = None;
+    ///     let event_id = CaptureReportBuilder::new(&report)
+    ///         .on_captured(|event_id| captured = Some(event_id))
+    ///         .capture();
+    ///
+    ///     assert_eq!(captured, Some(event_id));
+    /// });
+    /// ```
+    pub fn on_captured<F: FnOnce(Uuid) + 'a>(mut self, callback: F) -> Self {
+        self.on_captured = Some(Box::new(callback));
+        self
+    }
+
+    /// Attaches the report's `Debug` rendering (i.e. with the sections of `color-eyre`) as the
+    /// `eyre-report.txt` attachment when the event is captured, since it's often more useful
+    /// than the exceptions alone. ANSI escape sequences are stripped off.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::EnvelopeItem;
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let envelopes = sentry::test::with_captured_envelopes(|| {
+    ///     let report = eyre::eyre!("migration failed");
+    ///     CaptureReportBuilder::new(&report).attach_debug_report().capture();
+    /// });
+    ///
+    /// let attachments = envelopes[0]
+    ///     .items()
+    ///     .filter_map(|item| match item {
+    ///         EnvelopeItem::Attachment(attachment) => Some(attachment),
+    ///         _ => None,
+    ///     })
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(attachments.len(), 1);
+    /// assert_eq!(attachments[0].filename, "eyre-report.txt");
+    /// assert_eq!(attachments[0].content_type.as_deref(), Some("text/plain"));
+    /// ```
+    pub fn attach_debug_report(mut self) -> Self {
+        self.attach_debug_report = true;
+        self
+    }
+
+    /// Captures the configured event with the currently active [`Hub`].
+    pub fn capture(mut self) -> Uuid {
+        Hub::with_active(|hub| {
+            if !should_capture(hub, self.report) {
+                return Uuid::nil();
+            }
+
+            let report = self.report;
+            let on_captured = self.on_captured.take();
+            let attach_debug_report = self.attach_debug_report;
+            let max_payload_bytes = self.options.max_payload_bytes;
+            let event_id = hooks::capture_event_with(
+                hub,
+                report,
+                self.build(),
+                attach_debug_report,
+                max_payload_bytes,
+            );
+            if let Some(callback) = on_captured {
+                callback(event_id);
+            }
+
+            event_id
+        })
+    }
+
+    /// Builds the configured event without capturing it, i.e. to send it through a different
+    /// path like a batching transport. The event is identical to the one that [`capture`]
+    /// would send; the [`on_captured`] callback is not run.
+    ///
+    /// [`capture`]: CaptureReportBuilder::capture
+    /// [`on_captured`]: CaptureReportBuilder::on_captured
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Level;
+    /// use sentry_eyre::CaptureReportBuilder;
+    ///
+    /// let report = eyre::eyre!("batch failed");
+    /// let builder = || {
+    ///     CaptureReportBuilder::new(&report)
+    ///         .level(Level::Warning)
+    ///         .dist("42")
+    ///         .extra("batch", 7)
+    /// };
+    ///
+    /// let built = builder().build();
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     builder().capture();
+    /// });
+    ///
+    /// assert_eq!(built.level, events[0].level);
+    /// assert_eq!(built.dist, events[0].dist);
+    /// assert_eq!(built.extra, events[0].extra);
+    /// assert_eq!(built.exception, events[0].exception);
+    /// ```
+    pub fn build(mut self) -> Event<'static> {
+        // The payload is trimmed once everything was added onto the event.
+        let max_payload_bytes = self.options.max_payload_bytes.take();
+        let mut event = event_from_report_with_options(self.report, &self.options);
+        self.options.max_payload_bytes = max_payload_bytes;
+
+        if let Some(level) = self.level {
+            event.level = level;
+        }
+
+        if let Some(fingerprint) = self.fingerprint {
+            event.fingerprint = fingerprint.into();
+        }
+
+        event.tags.extend(self.tags);
+        event.extra.extend(self.extra);
+
+        for (index, data) in self.exception_data {
+            if let Some(exc) = event.exception.get_mut(index) {
+                exc.mechanism
+                    .get_or_insert_with(|| Mechanism {
+                        ty: "eyre".into(),
+                        ..Default::default()
+                    })
+                    .data
+                    .extend(data);
+            }
+        }
+
+        if let Some(id) = self.thread_id {
+            for exc in event.exception.iter_mut() {
+                exc.thread_id = Some(ThreadId::String(id.clone()));
+            }
+
+            event.threads.values.push(Thread {
+                id: Some(ThreadId::String(id)),
+                current: true,
+                ..Default::default()
+            });
+        }
+
+        if let Some(request) = self.request {
+            event.request = Some(request);
+        }
+
+        if let Some(log_entry) = self.log_entry {
+            event.logentry = Some(log_entry);
+        }
+
+        // Extras that were added on the builder need to be redacted as well.
+        self.options.redact(&mut event);
+        self.options.trim_payload(&mut event);
+        event
+    }
+}
@@ -0,0 +1,65 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture};
+use eyre::Report;
+use sentry_backtrace::parse_stacktrace;
+use sentry_core::{protocol::Thread, types::Uuid, Hub};
+use std::backtrace::Backtrace;
+
+/// Captures a [`Report`] along with the backtrace of where this function was called, which
+/// can differ a lot from where the error originated (i.e. when errors are propagated up to a
+/// central handler). The backtrace is attached as an additional thread named `capture site`.
+///
+/// The backtrace is captured with [`Backtrace::capture`], so it's only available when
+/// `RUST_BACKTRACE` (or `RUST_LIB_BACKTRACE`) is set; otherwise, the thread has no stacktrace.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_with_capture_site;
+///
+/// std::env::set_var("RUST_BACKTRACE", "1");
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_with_capture_site(&eyre::eyre!("job failed"));
+/// });
+///
+/// let thread = &events[0].threads.values[0];
+/// assert_eq!(thread.name.as_deref(), Some("capture site"));
+/// assert!(thread.stacktrace.is_some());
+/// ```
+pub fn capture_report_with_capture_site(report: &Report) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let backtrace = Backtrace::capture();
+        let mut event = event_from_report(report);
+        event.threads.values.push(Thread {
+            name: Some("capture site".into()),
+            stacktrace: parse_stacktrace(&backtrace.to_string()),
+            ..Default::default()
+        });
+
+        hooks::capture_event(hub, report, event)
+    })
+}
@@ -0,0 +1,79 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::options::sanitize;
+use color_eyre::Handler;
+use eyre::Report;
+use sentry_core::protocol::{Context, Event, Map, Value};
+use tracing_error::SpanTrace;
+
+/// The key of the context that the sections of the report are attached as.
+const SECTIONS_CONTEXT: &str = "color-eyre";
+
+/// The prefixes that [`color_eyre`] renders its help sections with, along with the key they
+/// are attached as in the sections context.
+const SECTION_PREFIXES: [(&str, &str); 3] = [
+    ("Note: ", "notes"),
+    ("Warning: ", "warnings"),
+    ("Suggestion: ", "suggestions"),
+];
+
+/// Attaches the help sections captured by [`color_eyre`]'s handler, if the report was created
+/// with it: the notes, warnings and suggestions are attached as the `color-eyre` context.
+pub(crate) fn attach_sections(report: &Report, event: &mut Event<'static>) {
+    if !report.handler().is::<Handler>() {
+        return;
+    }
+
+    let sections = sections_from_report(report);
+    if !sections.is_empty() {
+        event
+            .contexts
+            .insert(SECTIONS_CONTEXT.into(), Context::Other(sections));
+    }
+}
+
+/// Collects the notes, warnings and suggestions of the report from its `Debug` output, as the
+/// handler doesn't expose its sections.
+fn sections_from_report(report: &Report) -> Map<String, Value> {
+    let debug = sanitize(&format!("{report:?}"));
+    let mut sections = Map::new();
+
+    for (prefix, key) in SECTION_PREFIXES {
+        let values = debug
+            .lines()
+            .filter_map(|line| line.strip_prefix(prefix))
+            .map(Value::from)
+            .collect::<Vec<_>>();
+
+        if !values.is_empty() {
+            sections.insert(key.into(), values.into());
+        }
+    }
+
+    sections
+}
+
+/// Returns the `SpanTrace` captured by [`color_eyre`]'s handler, if the report was created
+/// with it.
+pub(crate) fn span_trace_from_report(report: &Report) -> Option<&SpanTrace> {
+    report.handler().downcast_ref::<Handler>()?.span_trace()
+}
@@ -0,0 +1,81 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use sentry_core::protocol::Map;
+use std::cell::RefCell;
+
+thread_local! {
+    static CONTEXT_STACK: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Pushes a context frame onto the current thread's context stack. All frames on the
+/// stack are merged into the tags of events that are created on this thread, where
+/// frames that were pushed later take precedence.
+///
+/// Every call must be balanced with a call to [`pop_context`]; prefer [`with_context`]
+/// which does so automatically.
+pub fn push_context<K: Into<String>, V: Into<String>>(key: K, value: V) {
+    CONTEXT_STACK.with_borrow_mut(|stack| stack.push((key.into(), value.into())));
+}
+
+/// Pops the last context frame that was pushed with [`push_context`] off the current
+/// thread's context stack.
+pub fn pop_context() -> Option<(String, String)> {
+    CONTEXT_STACK.with_borrow_mut(|stack| stack.pop())
+}
+
+/// Runs the given closure with a context frame pushed onto the current thread's context
+/// stack, popping it afterwards (even if the closure panics).
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, with_context};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     with_context("operation", "sync", || {
+///         with_context("tenant", "acme", || capture_report(&eyre::eyre!("sync failed")))
+///     });
+/// });
+///
+/// assert_eq!(events[0].tags["operation"], "sync");
+/// assert_eq!(events[0].tags["tenant"], "acme");
+/// ```
+pub fn with_context<K: Into<String>, V: Into<String>, F: FnOnce() -> R, R>(
+    key: K,
+    value: V,
+    f: F,
+) -> R {
+    struct PopGuard;
+    impl Drop for PopGuard {
+        fn drop(&mut self) {
+            pop_context();
+        }
+    }
+
+    push_context(key, value);
+    let _guard = PopGuard;
+    f()
+}
+
+/// Merges the current thread's context stack into the given tags.
+pub(crate) fn merge_into(tags: &mut Map<String, String>) {
+    CONTEXT_STACK.with_borrow(|stack| tags.extend(stack.iter().cloned()));
+}
@@ -0,0 +1,104 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::event_from_report;
+use eyre::Report;
+use sentry_core::protocol::{Envelope, Event};
+use std::{error::Error, fmt};
+
+/// Synthetic error that represents a single exception of an [`Event`].
+#[derive(Debug)]
+struct EventException {
+    ty: String,
+    value: Option<String>,
+    source: Option<Box<EventException>>,
+}
+
+impl fmt::Display for EventException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.value.as_deref().unwrap_or(&self.ty))
+    }
+}
+
+impl Error for EventException {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn Error + 'static))
+    }
+}
+
+/// Reconstructs a minimal [`Report`] from a (previously serialized) Sentry [`Event`]. The
+/// chain of the report is rebuilt from the event's exceptions, from outermost to innermost;
+/// if the event doesn't have any exceptions, its message is used instead.
+///
+/// The reconstructed report doesn't carry the original backtrace.
+///
+/// ## Example
+/// ```
+/// use eyre::WrapErr;
+/// use sentry_eyre::{event_from_report, report_from_event};
+///
+/// let report = Err::<(), _>(eyre::eyre!("inner")).wrap_err("outer").unwrap_err();
+/// let replayed = report_from_event(&event_from_report(&report));
+///
+/// assert_eq!(
+///     replayed.chain().map(ToString::to_string).collect::<Vec<_>>(),
+///     ["outer", "inner"]
+/// );
+/// ```
+pub fn report_from_event(event: &Event) -> Report {
+    let root = event.exception.iter().fold(None, |source, exc| {
+        Some(Box::new(EventException {
+            ty: exc.ty.clone(),
+            value: exc.value.clone(),
+            source,
+        }))
+    });
+
+    match root {
+        Some(root) => Report::new(*root),
+        None => Report::msg(
+            event
+                .message
+                .clone()
+                .unwrap_or_else(|| String::from("unknown error")),
+        ),
+    }
+}
+
+/// Builds an [`Envelope`] that contains the event of the given [`Report`], as created by
+/// [`event_from_report`]. This is useful to feed reports into a custom transport or to
+/// persist them.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::envelope_from_report;
+///
+/// let envelope = envelope_from_report(&eyre::eyre!("oh no"));
+/// assert_eq!(envelope.items().count(), 1);
+///
+/// let event = envelope.event().unwrap();
+/// assert_eq!(event.exception[0].value.as_deref(), Some("oh no"));
+/// ```
+pub fn envelope_from_report(report: &Report) -> Envelope {
+    Envelope::from(event_from_report(report))
+}
@@ -0,0 +1,475 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, fingerprint::hash_report, hooks, should_capture};
+use eyre::Report;
+use sentry_core::{types::Uuid, Hub};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How many reports [`capture_report_once`] remembers per thread.
+const CAPTURED_ONCE_CAPACITY: usize = 128;
+
+thread_local! {
+    static CAPTURED_ONCE: RefCell<VecDeque<(usize, u64, Uuid)>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// Captures a [`Report`] only if this exact report wasn't already captured with this function
+/// on the current thread, returning the original event's id otherwise. This avoids duplicate
+/// events when the same report flows through multiple layers that each try to capture it.
+///
+/// Reports are identified by the address of their error, so the same message from two
+/// different reports is captured twice. To guard against address reuse, only the last
+/// few captured reports are remembered and the report's chain has to match as well.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_once;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::eyre!("upload failed");
+///     let event_id = capture_report_once(&report);
+///
+///     assert_eq!(capture_report_once(&report), event_id);
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn capture_report_once(report: &Report) -> Uuid {
+    let hub = Hub::current();
+    if !should_capture(&hub, report) {
+        return Uuid::nil();
+    }
+
+    let address = report.as_ref() as *const dyn std::error::Error as *const () as usize;
+    let hash = hash_report(report);
+
+    let captured = CAPTURED_ONCE.with_borrow(|captured| {
+        captured
+            .iter()
+            .find(|(addr, h, _)| *addr == address && *h == hash)
+            .map(|(_, _, event_id)| *event_id)
+    });
+
+    if let Some(event_id) = captured {
+        return event_id;
+    }
+
+    // The report is captured directly, since checking whether to capture it again could make
+    // a different sampling decision.
+    let event_id = hooks::capture_event(&hub, report, event_from_report(report));
+    if !event_id.is_nil() {
+        CAPTURED_ONCE.with_borrow_mut(|captured| {
+            if captured.len() == CAPTURED_ONCE_CAPACITY {
+                captured.pop_front();
+            }
+
+            captured.push_back((address, hash, event_id));
+        });
+    }
+
+    event_id
+}
+
+/// How many reports [`capture_report_scoped_once`] remembers per scope.
+const SCOPED_ONCE_CAPACITY: usize = 128;
+
+/// The hashes of the reports that were captured with [`capture_report_scoped_once`] in a
+/// scope.
+type ScopedOnce = Mutex<VecDeque<u64>>;
+
+/// The states of [`capture_report_scoped_once`] by the address of their [`Hub`]. Every state
+/// is owned by an event processor of the scope it belongs to, so it's gone once that scope
+/// is popped.
+static SCOPED_ONCE: Mutex<Vec<(usize, Weak<ScopedOnce>)>> = Mutex::new(Vec::new());
+
+/// Captures a [`Report`] only if a report with the same chain wasn't already captured with
+/// this function in the current scope, returning a nil id otherwise. This avoids duplicate
+/// events within a single request, like retries that all fail identically, while identical
+/// reports in other scopes (i.e. other requests) are still captured.
+///
+/// The captured reports are remembered by an event processor that is added to the current
+/// scope, so they're forgotten once the scope is popped, i.e. at the end of
+/// [`with_scope`](sentry_core::with_scope). Nested scopes share them with the scope they're
+/// pushed in, and outside of a pushed scope they're remembered by the hub's root scope. Only
+/// the last few captured reports are remembered per scope.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_scoped_once;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     sentry::with_scope(|_| {}, || {
+///         for _ in 0..3 {
+///             capture_report_scoped_once(&eyre::eyre!("upstream timed out"));
+///         }
+///     });
+///
+///     sentry::with_scope(|_| {}, || {
+///         capture_report_scoped_once(&eyre::eyre!("upstream timed out"));
+///     });
+/// });
+///
+/// assert_eq!(events.len(), 2);
+/// ```
+pub fn capture_report_scoped_once(report: &Report) -> Uuid {
+    Hub::with_active(|hub| {
+        let key = Arc::as_ptr(hub) as usize;
+        let hash = hash_report(report);
+
+        let state = scoped_once_state(key);
+        if state
+            .as_ref()
+            .is_some_and(|state| state.lock().unwrap().contains(&hash))
+        {
+            return Uuid::nil();
+        }
+
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let event_id = hooks::capture_event(hub, report, event_from_report(report));
+        if !event_id.is_nil() {
+            let state = state.unwrap_or_else(|| {
+                let state = Arc::new(ScopedOnce::default());
+                SCOPED_ONCE
+                    .lock()
+                    .unwrap()
+                    .push((key, Arc::downgrade(&state)));
+
+                let owned = state.clone();
+                hub.configure_scope(|scope| {
+                    scope.add_event_processor(move |event| {
+                        // The processor only keeps the state alive for as long as the scope.
+                        let _ = &owned;
+                        Some(event)
+                    })
+                });
+
+                state
+            });
+
+            let mut captured = state.lock().unwrap();
+            if captured.len() == SCOPED_ONCE_CAPACITY {
+                captured.pop_front();
+            }
+
+            captured.push_back(hash);
+        }
+
+        event_id
+    })
+}
+
+/// Returns the state of [`capture_report_scoped_once`] for the current scope of the hub with
+/// the given address, if any, and forgets the states of popped scopes.
+fn scoped_once_state(key: usize) -> Option<Arc<ScopedOnce>> {
+    let mut states = SCOPED_ONCE.lock().unwrap();
+    states.retain(|(_, state)| state.strong_count() > 0);
+    states
+        .iter()
+        .rev()
+        .find(|(hub, _)| *hub == key)
+        .and_then(|(_, state)| state.upgrade())
+}
+
+/// Persistent store of reports that were captured recently, which is used to avoid reporting
+/// the same error again within a time window, even across process restarts. This is meant
+/// for CLI tools that run repeatedly.
+///
+/// The store is a plain text file where every line holds the hash of a report's chain and the
+/// time it was last captured.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::DedupStore;
+///
+/// let path = std::env::temp_dir().join(format!("sentry-eyre-dedup-{}", std::process::id()));
+/// let events = sentry::test::with_captured_events(|| {
+///     let store = DedupStore::open(&path).unwrap();
+///     assert!(store.capture_report(&eyre::eyre!("config is invalid")).is_some());
+///
+///     // ...the next run of the tool
+///     let store = DedupStore::open(&path).unwrap();
+///     assert!(store.capture_report(&eyre::eyre!("config is invalid")).is_none());
+/// });
+///
+/// assert_eq!(events.len(), 1);
+///
+/// // without a client, nothing is captured
+/// let store = DedupStore::open(&path).unwrap();
+/// assert!(store.capture_report(&eyre::eyre!("network is down")).is_none());
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct DedupStore {
+    path: PathBuf,
+    window: Duration,
+    entries: Mutex<HashMap<u64, u64>>,
+}
+
+impl DedupStore {
+    /// The default window in which a report isn't captured again (one day).
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60 * 60 * 24);
+
+    /// Opens the store at the given path, which is created once a report is captured if
+    /// it doesn't exist yet.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+
+        let entries = contents
+            .lines()
+            .filter_map(|line| {
+                let (hash, timestamp) = line.split_once(' ')?;
+                Some((u64::from_str_radix(hash, 16).ok()?, timestamp.parse().ok()?))
+            })
+            .collect();
+
+        Ok(DedupStore {
+            path,
+            window: Self::DEFAULT_WINDOW,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Sets the window in which a report isn't captured again, which is
+    /// [one day](Self::DEFAULT_WINDOW) by default.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Captures a [`Report`] unless it was already captured within the window. Returns
+    /// [`None`] if the report was skipped, either as a repeat or since it isn't captured
+    /// at all (i.e. it was suppressed or sampled out).
+    ///
+    /// Persisting the store is best-effort: if it can't be written, the report is still
+    /// captured but might be captured again by the next run.
+    pub fn capture_report(&self, report: &Report) -> Option<Uuid> {
+        let hub = Hub::current();
+        if !should_capture(&hub, report) {
+            return None;
+        }
+
+        let hash = hash_report(report);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries
+                .retain(|_, captured_at| now.saturating_sub(*captured_at) < self.window.as_secs());
+
+            if entries.contains_key(&hash) {
+                return None;
+            }
+        }
+
+        // The lock isn't held while capturing, since that runs the hooks.
+        let id = hooks::capture_event(&hub, report, event_from_report(report));
+        if !id.is_nil() {
+            let mut entries = self.entries.lock().unwrap();
+            entries.insert(hash, now);
+
+            let contents: String = entries
+                .iter()
+                .map(|(hash, captured_at)| format!("{hash:016x} {captured_at}\n"))
+                .collect();
+
+            let _ = fs::write(&self.path, contents);
+        }
+
+        Some(id)
+    }
+}
+
+/// The outcome of [`Deduplicator::capture_report`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupOutcome {
+    /// The report was captured with the given event id, which is nil if the report was
+    /// dropped by a hook or no client is bound.
+    Captured(Uuid),
+
+    /// The report was dropped since it repeats a report that was captured within the window.
+    Suppressed,
+}
+
+impl DedupOutcome {
+    /// Returns the id of the captured event, or a nil id if the report was suppressed.
+    pub fn event_id(&self) -> Uuid {
+        match self {
+            DedupOutcome::Captured(id) => *id,
+            DedupOutcome::Suppressed => Uuid::nil(),
+        }
+    }
+
+    /// Returns whether the report was suppressed.
+    pub fn is_suppressed(&self) -> bool {
+        matches!(self, DedupOutcome::Suppressed)
+    }
+}
+
+/// Key of the `extra` entry that holds how many repeats of a report were suppressed by a
+/// [`Deduplicator`] since it was last captured.
+const SUPPRESSED_REPEATS_KEY: &str = "suppressed_repeats";
+
+/// Throttles reports that repeat within a time window, i.e. in hot retry loops, so they
+/// don't exhaust the quota. Reports are identified by a hash of their chain, so the same
+/// error created in different places counts as a repeat.
+///
+/// The first report starts the window, and repeats within it are dropped (or sampled with
+/// [`with_repeat_rate`](Self::with_repeat_rate)). The next report that's captured holds how
+/// many repeats were suppressed as the `suppressed_repeats` extra. Unlike [`DedupStore`],
+/// the deduplicator only lives in memory.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::Deduplicator;
+/// use std::time::Duration;
+///
+/// let dedup = Deduplicator::new(Duration::from_secs(60));
+/// let events = sentry::test::with_captured_events(|| {
+///     for attempt in 0..5 {
+///         let outcome = dedup.capture_report(&eyre::eyre!("upstream timed out"));
+///         assert_eq!(outcome.is_suppressed(), attempt > 0);
+///     }
+///
+///     assert!(!dedup.capture_report(&eyre::eyre!("disk full")).is_suppressed());
+/// });
+///
+/// assert_eq!(events.len(), 2);
+/// ```
+pub struct Deduplicator {
+    window: Duration,
+    repeat_rate: f32,
+    entries: Mutex<HashMap<u64, DedupEntry>>,
+}
+
+struct DedupEntry {
+    captured_at: Instant,
+    suppressed: u64,
+}
+
+impl Deduplicator {
+    /// Creates a new [`Deduplicator`] that suppresses repeats of a report within the given
+    /// window.
+    pub fn new(window: Duration) -> Self {
+        Deduplicator {
+            window,
+            repeat_rate: 0.0,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the rate (clamped to `0.0..=1.0`) at which repeats within the window are still
+    /// captured, rather than dropping all of them. Sampled repeats don't restart the window.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::Deduplicator;
+    /// use std::time::Duration;
+    ///
+    /// let dedup = Deduplicator::new(Duration::from_secs(60)).with_repeat_rate(1.0);
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     for _ in 0..3 {
+    ///         assert!(!dedup.capture_report(&eyre::eyre!("retrying")).is_suppressed());
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(events.len(), 3);
+    /// ```
+    pub fn with_repeat_rate(mut self, rate: f32) -> Self {
+        self.repeat_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Captures a [`Report`] unless it repeats a report that was captured within the window.
+    pub fn capture_report(&self, report: &Report) -> DedupOutcome {
+        let hub = Hub::current();
+        if !should_capture(&hub, report) {
+            return DedupOutcome::Captured(Uuid::nil());
+        }
+
+        let hash = hash_report(report);
+        let now = Instant::now();
+        let (suppressed, restart_window) = {
+            let mut entries = self.entries.lock().unwrap();
+
+            // Expired entries are kept until the report is captured again if repeats of it
+            // were suppressed, so the next event still tells how many.
+            entries.retain(|_, entry| {
+                entry.suppressed > 0 || now.duration_since(entry.captured_at) < self.window
+            });
+
+            match entries.get_mut(&hash) {
+                Some(entry) if now.duration_since(entry.captured_at) < self.window => {
+                    let sampled = hub
+                        .client()
+                        .is_some_and(|client| client.sample_should_send(self.repeat_rate));
+
+                    if !sampled {
+                        entry.suppressed += 1;
+                        return DedupOutcome::Suppressed;
+                    }
+
+                    (std::mem::take(&mut entry.suppressed), false)
+                }
+
+                Some(entry) => (std::mem::take(&mut entry.suppressed), true),
+                None => (0, true),
+            }
+        };
+
+        let mut event = event_from_report(report);
+        if suppressed > 0 {
+            event
+                .extra
+                .insert(SUPPRESSED_REPEATS_KEY.into(), suppressed.into());
+        }
+
+        // The lock isn't held while capturing, since that runs the hooks.
+        let id = hooks::capture_event(&hub, report, event);
+        if !id.is_nil() && restart_window {
+            self.entries.lock().unwrap().insert(
+                hash,
+                DedupEntry {
+                    captured_at: now,
+                    suppressed: 0,
+                },
+            );
+        }
+
+        DedupOutcome::Captured(id)
+    }
+}
@@ -0,0 +1,176 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{apply_options, attach_mechanisms, hooks, mapper, options, should_capture_error};
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode};
+use sentry_core::{
+    event_from_error,
+    protocol::{Event, Level, Value},
+    types::Uuid,
+    Hub,
+};
+use std::error::Error;
+
+/// Captures a [`miette::Report`] and sends it to Sentry, the same way as
+/// [`capture_report`](crate::capture_report) does for [`eyre::Report`]s, including the
+/// [`before_capture`](crate::add_before_capture) hooks. Refer to [`event_from_diagnostic`] on
+/// how the diagnostic's metadata is represented.
+///
+/// **Feature:** `miette` (*disabled by default*)
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_diagnostic;
+///
+/// sentry_eyre::add_before_capture(|_, mut event| {
+///     event.tags.insert("scrubbed".into(), "true".into());
+///     Some(event)
+/// });
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_diagnostic(&miette::miette!(severity = miette::Severity::Warning, "deprecated key"));
+/// });
+///
+/// assert_eq!(events[0].level, Level::Warning);
+/// assert_eq!(events[0].tags["scrubbed"], "true");
+/// assert_eq!(events[0].exception[0].value.as_deref(), Some("deprecated key"));
+/// ```
+pub fn capture_diagnostic(report: &miette::Report) -> Uuid {
+    Hub::with_active(|hub| {
+        let err: &(dyn Error + 'static) = report.as_ref();
+        if !should_capture_error(hub, err) {
+            return Uuid::nil();
+        }
+
+        hooks::capture_event(hub, err, event_from_diagnostic(report))
+    })
+}
+
+/// Utility function to represent a Sentry [`Event`] from a [`miette::Report`]. Just like
+/// [`event_from_report`](crate::event_from_report), every link of the chain is represented as
+/// its own exception, and the [current options](crate::with_options) are applied. On top of
+/// that:
+///
+/// - the diagnostic's code is set as the `diagnostic.code` tag;
+/// - its [`Severity`] is mapped to the event's [`Level`] (advices are informational);
+/// - its help text and URL are attached as the `diagnostic.help` and `diagnostic.url` extras;
+/// - its labels are attached as the `diagnostic.labels` extra, along with the (1-based)
+///   line, column and text of the labeled source span if the diagnostic has source code.
+///
+/// **Feature:** `miette` (*disabled by default*)
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::event_from_diagnostic;
+///
+/// let report = miette::miette!(
+///     code = "config::invalid_port",
+///     help = "ports need to be between 1 and 65535",
+///     labels = vec![miette::LabeledSpan::at(16..21, "this port")],
+///     "invalid port"
+/// )
+/// .with_source_code("[server]\nport = 99999\n");
+///
+/// let event = event_from_diagnostic(&report);
+/// assert_eq!(event.level, Level::Error);
+/// assert_eq!(event.tags["diagnostic.code"], "config::invalid_port");
+/// assert_eq!(event.extra["diagnostic.help"], "ports need to be between 1 and 65535");
+///
+/// let label = &event.extra["diagnostic.labels"][0];
+/// assert_eq!(label["label"], "this port");
+/// assert_eq!(label["line"], 2);
+/// assert_eq!(label["column"], 8);
+/// assert_eq!(label["text"], "99999");
+/// ```
+pub fn event_from_diagnostic(report: &miette::Report) -> Event<'static> {
+    let diagnostic: &dyn Diagnostic = report.as_ref();
+    let err: &(dyn Error + 'static) = report.as_ref();
+
+    let mut event = event_from_error(err);
+    attach_mechanisms(&mut event);
+
+    event.level = match diagnostic.severity() {
+        Some(Severity::Advice) => Level::Info,
+        Some(Severity::Warning) => Level::Warning,
+        Some(Severity::Error) | None => Level::Error,
+    };
+
+    if let Some(code) = diagnostic.code() {
+        event
+            .tags
+            .insert("diagnostic.code".into(), code.to_string());
+    }
+
+    if let Some(help) = diagnostic.help() {
+        event
+            .extra
+            .insert("diagnostic.help".into(), help.to_string().into());
+    }
+
+    if let Some(url) = diagnostic.url() {
+        event
+            .extra
+            .insert("diagnostic.url".into(), url.to_string().into());
+    }
+
+    if let Some(labels) = diagnostic.labels() {
+        let labels = labels
+            .map(|label| label_to_value(&label, diagnostic.source_code()))
+            .collect::<Vec<_>>();
+
+        if !labels.is_empty() {
+            event
+                .extra
+                .insert("diagnostic.labels".into(), Value::Array(labels));
+        }
+    }
+
+    apply_options(&mut event, &options::current_options());
+    mapper::apply(err, &mut event);
+
+    event
+}
+
+/// Represents a [`LabeledSpan`] of a diagnostic, resolving its location in the diagnostic's
+/// source code (if it has any).
+fn label_to_value(label: &LabeledSpan, source: Option<&dyn SourceCode>) -> Value {
+    let mut value = serde_json::Map::new();
+    if let Some(text) = label.label() {
+        value.insert("label".into(), text.into());
+    }
+
+    value.insert("offset".into(), label.offset().into());
+    value.insert("length".into(), label.len().into());
+    value.insert("primary".into(), label.primary().into());
+
+    if let Some(contents) = source.and_then(|source| source.read_span(label.inner(), 0, 0).ok()) {
+        value.insert("line".into(), (contents.line() + 1).into());
+        value.insert("column".into(), (contents.column() + 1).into());
+        value.insert(
+            "text".into(),
+            String::from_utf8_lossy(contents.data()).into_owned().into(),
+        );
+    }
+
+    Value::Object(value)
+}
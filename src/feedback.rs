@@ -0,0 +1,144 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use sentry_core::{protocol::Envelope, types::Uuid, Hub};
+use serde_json::json;
+use std::io::Write;
+
+/// Builder for the feedback of a user about an event that was captured before, i.e. after
+/// prompting them for details about what they were doing when a [`Report`](eyre::Report)
+/// was captured.
+///
+/// The feedback is sent as a `user_report` envelope item through the client of the current
+/// [`Hub`], so it's associated with the event in Sentry.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, UserFeedback};
+///
+/// let envelopes = sentry::test::with_captured_envelopes(|| {
+///     let event_id = capture_report(&eyre::eyre!("failed to save document"));
+///
+///     let sent = UserFeedback::new(event_id, "I clicked on save twice")
+///         .name("Noel")
+///         .email("noel@example.com")
+///         .send();
+///
+///     assert!(sent);
+/// });
+///
+/// let mut buf = Vec::new();
+/// envelopes[1].to_writer(&mut buf).unwrap();
+///
+/// let buf = String::from_utf8(buf).unwrap();
+/// assert!(buf.contains(r#""type":"user_report""#));
+/// assert!(buf.contains(r#""comments":"I clicked on save twice""#));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UserFeedback {
+    event_id: Uuid,
+    name: Option<String>,
+    email: Option<String>,
+    comments: String,
+}
+
+impl UserFeedback {
+    /// Creates a new [`UserFeedback`] with the user's comments about the event with the
+    /// given id, i.e. the one returned by [`capture_report`](crate::capture_report).
+    pub fn new<C: Into<String>>(event_id: Uuid, comments: C) -> Self {
+        UserFeedback {
+            event_id,
+            name: None,
+            email: None,
+            comments: comments.into(),
+        }
+    }
+
+    /// Sets the name of the user who sent the feedback.
+    pub fn name<N: Into<String>>(mut self, name: N) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the email address of the user who sent the feedback.
+    pub fn email<E: Into<String>>(mut self, email: E) -> Self {
+        self.email = Some(email.into());
+        self
+    }
+
+    /// Sends the feedback through the client of the current [`Hub`]. Returns `false` if no
+    /// client is bound or the event id is nil (i.e. when the event wasn't captured).
+    pub fn send(self) -> bool {
+        self.send_with(&Hub::current())
+    }
+
+    /// Same as [`send`](Self::send), but sends the feedback through the client of the given
+    /// [`Hub`].
+    pub fn send_with(self, hub: &Hub) -> bool {
+        if self.event_id.is_nil() {
+            return false;
+        }
+
+        let Some(client) = hub.client().filter(|client| client.is_enabled()) else {
+            return false;
+        };
+
+        match self.to_envelope() {
+            Some(envelope) => {
+                client.send_envelope(envelope);
+                true
+            }
+
+            None => false,
+        }
+    }
+
+    /// Serializes the feedback as an envelope with a single `user_report` item.
+    fn to_envelope(&self) -> Option<Envelope> {
+        let event_id = self.event_id.simple().to_string();
+        let item = serde_json::to_vec(&json!({
+            "event_id": event_id,
+            "name": self.name.as_deref().unwrap_or_default(),
+            "email": self.email.as_deref().unwrap_or_default(),
+            "comments": self.comments,
+        }))
+        .ok()?;
+
+        let mut buf = Vec::new();
+        writeln!(buf, r#"{{"event_id":"{event_id}"}}"#).ok()?;
+        writeln!(buf, r#"{{"type":"user_report","length":{}}}"#, item.len()).ok()?;
+
+        buf.extend(item);
+        buf.push(b'\n');
+
+        Envelope::from_bytes_raw(buf).ok()
+    }
+}
+
+/// Sends the feedback of a user about the event with the given id through the client of the
+/// current [`Hub`]. Refer to [`UserFeedback`] for an example and to set only some of the
+/// user's details.
+pub fn send_user_feedback(event_id: Uuid, name: &str, email: &str, comments: &str) -> bool {
+    UserFeedback::new(event_id, comments)
+        .name(name)
+        .email(email)
+        .send()
+}
@@ -0,0 +1,182 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::backtrace::stacktrace_from_report;
+use eyre::Report;
+use sentry_core::protocol::Frame;
+use std::{borrow::Cow, error::Error};
+
+/// A Sentry fingerprint, which controls how events are grouped into issues.
+///
+/// Fingerprints can contain the special `{{ default }}` component, which stands for the
+/// grouping Sentry would use by default; this lets custom components refine the grouping
+/// rather than fully overriding it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Fingerprint(Vec<Cow<'static, str>>);
+
+impl Fingerprint {
+    /// The special fingerprint component that stands for Sentry's default grouping.
+    pub const DEFAULT: &'static str = "{{ default }}";
+
+    /// Creates a new [`Fingerprint`] from the given components.
+    pub fn new<I: IntoIterator<Item = S>, S: Into<Cow<'static, str>>>(components: I) -> Self {
+        Fingerprint(components.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a new [`Fingerprint`] that refines Sentry's default grouping with the given
+    /// components, i.e. `["{{ default }}", extra...]`.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{CaptureReportBuilder, Fingerprint};
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("query timed out");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .fingerprint(Fingerprint::with_default(&["db"]))
+    ///         .capture();
+    /// });
+    ///
+    /// assert_eq!(events[0].fingerprint[..], ["{{ default }}", "db"]);
+    /// ```
+    pub fn with_default(extra: &[&str]) -> Self {
+        let mut components = Vec::with_capacity(extra.len() + 1);
+        components.push(Cow::Borrowed(Self::DEFAULT));
+        components.extend(
+            extra
+                .iter()
+                .map(|component| Cow::Owned(component.to_string())),
+        );
+
+        Fingerprint(components)
+    }
+
+    /// Returns the components of this fingerprint.
+    pub fn components(&self) -> &[Cow<'static, str>] {
+        &self.0
+    }
+}
+
+impl<S: Into<Cow<'static, str>>, const N: usize> From<[S; N]> for Fingerprint {
+    fn from(components: [S; N]) -> Self {
+        Fingerprint::new(components)
+    }
+}
+
+impl<S: Into<Cow<'static, str>>> From<Vec<S>> for Fingerprint {
+    fn from(components: Vec<S>) -> Self {
+        Fingerprint::new(components)
+    }
+}
+
+impl IntoIterator for Fingerprint {
+    type Item = Cow<'static, str>;
+    type IntoIter = std::vec::IntoIter<Cow<'static, str>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl From<Fingerprint> for Cow<'static, [Cow<'static, str>]> {
+    fn from(fingerprint: Fingerprint) -> Self {
+        Cow::Owned(fingerprint.0)
+    }
+}
+
+/// Crates whose frames are never considered to be in-app when deriving a fingerprint from a
+/// backtrace.
+const NON_IN_APP_CRATES: &[&str] = &[
+    "alloc",
+    "backtrace",
+    "color_eyre",
+    "core",
+    "eyre",
+    "sentry_backtrace",
+    "sentry_core",
+    "sentry_eyre",
+    "stable_eyre",
+    "std",
+];
+
+/// Derives a fingerprint from the report's backtrace, made up of the `function` and `lineno`
+/// of the innermost in-app frame. This groups reports by *where* they were created rather
+/// than by their (possibly dynamic) message.
+///
+/// Frames that were explicitly marked as in-app win; otherwise, frames from the standard
+/// library, `eyre` and this crate are skipped. Returns `None` when the report has no
+/// backtrace (i.e. no backtrace backend is enabled) or when there is no in-app frame.
+pub fn fingerprint_from_backtrace(report: &Report) -> Option<Vec<String>> {
+    let stacktrace = stacktrace_from_report(report)?;
+    let frame = stacktrace
+        .frames
+        .iter()
+        .rev()
+        .find(|frame| frame.in_app == Some(true))
+        .or_else(|| {
+            stacktrace
+                .frames
+                .iter()
+                .rev()
+                .find(|frame| frame.in_app.is_none() && is_probably_in_app(frame))
+        })?;
+
+    let mut fingerprint = vec![frame.function.clone()?];
+    if let Some(lineno) = frame.lineno {
+        fingerprint.push(lineno.to_string());
+    }
+
+    Some(fingerprint)
+}
+
+pub(crate) fn is_probably_in_app(frame: &Frame) -> bool {
+    let Some(function) = frame.function.as_deref() else {
+        return false;
+    };
+
+    // Trait impls are demangled as `<T as Trait>::method`, so look at the implementing type.
+    let path = function.trim_start_matches('<');
+    match path.split_once("::") {
+        Some((krate, _)) => !NON_IN_APP_CRATES.contains(&krate),
+
+        // Symbols without a path are usually from the runtime (i.e. `__libc_start_main`).
+        None => false,
+    }
+}
+
+/// Computes a stable hash of the report's chain. Unlike [`std::hash::DefaultHasher`], this
+/// hash (64-bit FNV-1a) is guaranteed to be the same across Rust versions and process
+/// restarts, so it can be persisted.
+pub(crate) fn hash_report(report: &Report) -> u64 {
+    hash_error(report.as_ref())
+}
+
+/// Same as [`hash_report`], but for the chain of any error.
+pub(crate) fn hash_error(err: &(dyn Error + 'static)) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    std::iter::successors(Some(err), |&err| err.source())
+        .flat_map(|err| err.to_string().into_bytes().into_iter().chain([0]))
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
@@ -0,0 +1,114 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::CaptureReportExt;
+use eyre::Report;
+use pin_project_lite::pin_project;
+use sentry_core::{types::Uuid, Hub};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+/// Captures a [`Report`] on the given [`Hub`] instead of the active one. In async code,
+/// the active hub after an `.await` point isn't necessarily the one of the task that the
+/// report belongs to, so the hub should be bound when the task is created and passed along.
+///
+/// ## Example
+/// ```
+/// use sentry::Hub;
+/// use sentry_eyre::capture_report_on;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let hub = Hub::current();
+///     capture_report_on(hub, &eyre::eyre!("request failed"));
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn capture_report_on(hub: Arc<Hub>, report: &Report) -> Uuid {
+    hub.capture_report(report)
+}
+
+/// Extension trait for futures that resolve to a [`Result`] with a [`Report`], which captures
+/// the error once the future resolves.
+///
+/// This is similar to `SentryFutureExt::bind_hub`, but also captures the error: the
+/// [current hub](Hub::current) is bound when the combinator is created, and is made the active
+/// one while the future is polled.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::FutureReportExt;
+/// use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let fut = pin!(async { Err::<(), _>(eyre::eyre!("job failed")) }.capture_err());
+///     let poll = fut.poll(&mut Context::from_waker(Waker::noop()));
+///
+///     assert!(matches!(poll, Poll::Ready(Err(_))));
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub trait FutureReportExt<T>: Future<Output = Result<T, Report>> + Sized {
+    /// Captures the [`Report`] that the future resolves to on the current hub, if any. The
+    /// result is passed through as-is.
+    fn capture_err(self) -> CaptureErr<Self>;
+}
+
+impl<T, F: Future<Output = Result<T, Report>>> FutureReportExt<T> for F {
+    fn capture_err(self) -> CaptureErr<Self> {
+        CaptureErr {
+            hub: Hub::current(),
+            future: self,
+        }
+    }
+}
+
+pin_project! {
+    /// A future that captures the [`Report`] its inner future resolves to, created by
+    /// [`FutureReportExt::capture_err`].
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct CaptureErr<F> {
+        hub: Arc<Hub>,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<T, F: Future<Output = Result<T, Report>>> Future for CaptureErr<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let hub = this.hub.clone();
+
+        let poll = Hub::run(hub.clone(), || this.future.poll(cx));
+        if let Poll::Ready(Err(report)) = &poll {
+            hub.capture_report(report);
+        }
+
+        poll
+    }
+}
@@ -0,0 +1,84 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report_with_options, hooks, should_capture, EventOptions};
+use eyre::Report;
+use sentry_core::{types::Uuid, Hub};
+use std::sync::Arc;
+
+/// Handle that captures reports with [`EventOptions`] that are configured once, i.e. at
+/// startup. This is meant for high-frequency capture sites of long-running processes, where
+/// the work per capture is only creating and sending the event.
+///
+/// The handle is cheap to clone and can be shared between threads.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{CaptureHandle, EventOptions};
+///
+/// let handle = CaptureHandle::new(EventOptions {
+///     platform: Some("daemon".into()),
+///     ..Default::default()
+/// });
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     handle.capture(&eyre::eyre!("tick failed"));
+///     handle.capture(&eyre::eyre!("tick failed again"));
+/// });
+///
+/// assert_eq!(events.len(), 2);
+/// assert!(events.iter().all(|event| event.platform == "daemon"));
+/// ```
+#[derive(Clone)]
+pub struct CaptureHandle {
+    options: Arc<EventOptions>,
+}
+
+impl CaptureHandle {
+    /// Creates a new [`CaptureHandle`] that captures reports with the given [`EventOptions`].
+    pub fn new(options: EventOptions) -> Self {
+        CaptureHandle {
+            options: Arc::new(options),
+        }
+    }
+
+    /// Returns the [`EventOptions`] that this handle captures reports with.
+    pub fn options(&self) -> &EventOptions {
+        &self.options
+    }
+
+    /// Captures a [`Report`] with the currently active [`Hub`].
+    pub fn capture(&self, report: &Report) -> Uuid {
+        Hub::with_active(|hub| {
+            if !should_capture(hub, report) {
+                return Uuid::nil();
+            }
+
+            hooks::capture_event_with(
+                hub,
+                report,
+                event_from_report_with_options(report, &self.options),
+                false,
+                self.options.max_payload_bytes,
+            )
+        })
+    }
+}
@@ -0,0 +1,268 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    options::{self, sanitize},
+    performance, EyreIntegration,
+};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Attachment, Event},
+    types::Uuid,
+    Hub,
+};
+use std::{
+    error::Error,
+    sync::{Arc, RwLock},
+};
+
+pub(crate) type BeforeCapture =
+    Arc<dyn Fn(&(dyn Error + 'static), Event<'static>) -> Option<Event<'static>> + Send + Sync>;
+
+static BEFORE_CAPTURE: RwLock<Vec<BeforeCapture>> = RwLock::new(Vec::new());
+
+/// Registers a hook that runs right before an event that was created from a [`Report`] (or
+/// any other error) is captured, i.e. to enrich or scrub it. Hooks run in the order they were
+/// registered, each receiving the event returned by the previous one; returning [`None`] drops
+/// the event entirely, in which case capturing returns a nil [`Uuid`].
+///
+/// Hooks receive the error the event was created from, which is the outermost error of the
+/// chain for a [`Report`]; the rest of the chain is available through its
+/// [`source`](Error::source)s.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{add_before_capture, capture_report};
+///
+/// add_before_capture(|_, mut event| {
+///     event.tags.insert("module".into(), "billing".into());
+///     Some(event)
+/// });
+///
+/// add_before_capture(|err, mut event| {
+///     let chain = std::iter::successors(Some(err), |&err| err.source());
+///     event.tags.insert("chain".into(), chain.count().to_string());
+///     Some(event)
+/// });
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report(&eyre::eyre!("invoice failed"));
+/// });
+///
+/// assert_eq!(events[0].tags["module"], "billing");
+/// assert_eq!(events[0].tags["chain"], "1");
+/// ```
+pub fn add_before_capture<F>(hook: F)
+where
+    F: Fn(&(dyn Error + 'static), Event<'static>) -> Option<Event<'static>> + Send + Sync + 'static,
+{
+    BEFORE_CAPTURE.write().unwrap().push(Arc::new(hook));
+}
+
+/// Removes all hooks that were registered with [`add_before_capture`].
+pub fn clear_before_capture() {
+    BEFORE_CAPTURE.write().unwrap().clear();
+}
+
+/// What an event that is passed through the hooks was created from.
+#[derive(Clone, Copy)]
+pub(crate) enum CaptureSource<'a> {
+    Report(&'a Report),
+    Error(&'a (dyn Error + 'static)),
+}
+
+impl<'a> CaptureSource<'a> {
+    /// Returns the (outermost) error the event was created from.
+    pub(crate) fn error(self) -> &'a (dyn Error + 'static) {
+        match self {
+            CaptureSource::Report(report) => report.as_ref(),
+            CaptureSource::Error(err) => err,
+        }
+    }
+
+    /// Renders the source with its `Debug` output, which is the handler's rendering (i.e. with
+    /// all sections of `color-eyre`) for a [`Report`].
+    fn debug(self) -> String {
+        match self {
+            CaptureSource::Report(report) => format!("{report:?}"),
+            CaptureSource::Error(err) => format!("{err:?}"),
+        }
+    }
+}
+
+impl<'a> From<&'a Report> for CaptureSource<'a> {
+    fn from(report: &'a Report) -> Self {
+        CaptureSource::Report(report)
+    }
+}
+
+impl<'a> From<&'a (dyn Error + 'static)> for CaptureSource<'a> {
+    fn from(err: &'a (dyn Error + 'static)) -> Self {
+        CaptureSource::Error(err)
+    }
+}
+
+/// Runs the registered hooks on the event and captures it with the given [`Hub`], unless
+/// a hook dropped it. The event is trimmed to the
+/// [`max_payload_bytes`](crate::EventOptions::max_payload_bytes) of the current options.
+pub(crate) fn capture_event<'a, S: Into<CaptureSource<'a>>>(
+    hub: &Hub,
+    source: S,
+    event: Event<'static>,
+) -> Uuid {
+    let max_payload_bytes = options::current_options().max_payload_bytes;
+    capture_event_with(hub, source, event, false, max_payload_bytes)
+}
+
+/// Runs the registered hooks, followed by the one of the [`EyreIntegration`] that is bound to
+/// the given [`Hub`], on the event. Returns [`None`] if a hook dropped it.
+pub(crate) fn run_hooks(
+    hub: &Hub,
+    err: &(dyn Error + 'static),
+    event: Event<'static>,
+) -> Option<Event<'static>> {
+    // Hooks are cloned out of the registry so that they can register hooks of their own
+    // without deadlocking.
+    let hooks = BEFORE_CAPTURE.read().unwrap().clone();
+    let integration_hook =
+        hub.with_integration(|integration: &EyreIntegration| integration.before_capture.clone());
+
+    hooks
+        .iter()
+        .chain(&integration_hook)
+        .try_fold(event, |event, hook| hook(err, event))
+}
+
+/// Same as [`capture_event`], but also attaches the source's `Debug` rendering to the event if
+/// `attach_report` is set or the bound [`EyreIntegration`] is configured to do so. A structured
+/// log is emitted alongside the event, and the active span is marked as failed, if the bound
+/// integration is configured to do so. The event is trimmed to `max_payload_bytes` once the
+/// scope was applied onto it.
+pub(crate) fn capture_event_with<'a, S: Into<CaptureSource<'a>>>(
+    hub: &Hub,
+    source: S,
+    event: Event<'static>,
+    attach_report: bool,
+    max_payload_bytes: Option<usize>,
+) -> Uuid {
+    prepare_event(hub, source, event, attach_report, max_payload_bytes)
+        .map_or(Uuid::nil(), |prepared| prepared.capture(hub))
+}
+
+/// An event that went through the hooks and is ready to be captured, along with what's
+/// captured alongside it. See [`prepare_event`].
+pub(crate) struct PreparedEvent {
+    event: Event<'static>,
+    attachment: Option<Attachment>,
+    max_payload_bytes: Option<usize>,
+    #[cfg(feature = "logs")]
+    log: Option<sentry_core::protocol::Log>,
+}
+
+impl PreparedEvent {
+    /// Returns the id that the event will have once it's captured.
+    pub(crate) fn event_id(&self) -> Uuid {
+        self.event.event_id
+    }
+
+    /// Captures the event with the given [`Hub`], along with its attachment and log.
+    pub(crate) fn capture(self, hub: &Hub) -> Uuid {
+        let event = self.event;
+        let event_id = match (self.attachment, self.max_payload_bytes) {
+            (None, None) => hub.capture_event(event),
+            (attachment, max_payload_bytes) => hub.with_scope(
+                |scope| {
+                    if let Some(attachment) = attachment {
+                        scope.add_attachment(attachment);
+                    }
+
+                    // The scope's processors run after its tags, extra and breadcrumbs were
+                    // applied, so this one bounds what's actually sent.
+                    if let Some(max) = max_payload_bytes {
+                        scope.add_event_processor(move |mut event| {
+                            options::trim_payload(&mut event, max);
+                            Some(event)
+                        });
+                    }
+                },
+                || hub.capture_event(event),
+            ),
+        };
+
+        #[cfg(feature = "logs")]
+        if let Some(log) = self.log {
+            crate::logs::capture_log(hub, log, event_id);
+        }
+
+        event_id
+    }
+}
+
+/// Does everything [`capture_event_with`] does, except for capturing the event, so that it
+/// can be captured later on (i.e. on another thread). Everything that depends on the source
+/// happens here, while the scope is only applied (and the event trimmed to
+/// `max_payload_bytes`) once the event is captured. Returns [`None`] if a hook dropped the
+/// event.
+pub(crate) fn prepare_event<'a, S: Into<CaptureSource<'a>>>(
+    hub: &Hub,
+    source: S,
+    event: Event<'static>,
+    attach_report: bool,
+    max_payload_bytes: Option<usize>,
+) -> Option<PreparedEvent> {
+    let source = source.into();
+    let err = source.error();
+    let mut event = run_hooks(hub, err, event)?;
+
+    let (integration_attach_report, fail_active_span) =
+        hub.with_integration(|integration: &EyreIntegration| {
+            (
+                integration.attach_debug_report,
+                integration.fail_active_span,
+            )
+        });
+
+    if fail_active_span {
+        performance::fail_active_span(hub, err, &mut event);
+    }
+
+    Some(PreparedEvent {
+        #[cfg(feature = "logs")]
+        log: crate::logs::emits_logs(hub).then(|| crate::logs::log_from_error(err, &event)),
+        attachment: (attach_report || integration_attach_report).then(|| report_attachment(source)),
+        max_payload_bytes,
+        event,
+    })
+}
+
+/// The name of the attachment that holds the report's `Debug` rendering.
+const REPORT_ATTACHMENT_NAME: &str = "eyre-report.txt";
+
+/// Renders the source with its `Debug` output as a plain text attachment, without ANSI escape
+/// sequences.
+fn report_attachment(source: CaptureSource<'_>) -> Attachment {
+    Attachment {
+        buffer: sanitize(&source.debug()).into_bytes(),
+        filename: REPORT_ATTACHMENT_NAME.into(),
+        content_type: Some("text/plain".into()),
+        ..Default::default()
+    }
+}
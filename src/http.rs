@@ -0,0 +1,96 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Helpers to build a Sentry [`Request`] context from [`http`] types.
+//!
+//! **Feature:** `http` (*disabled by default*)
+
+use http::{request::Parts, HeaderMap, Method, Uri};
+use sentry_core::protocol::{Request, Url};
+
+/// Builds a Sentry [`Request`] context from a [`http::Request`]. Only headers in the
+/// `allowed_headers` list (compared case-insensitively) are included, so credentials
+/// like `Authorization` or `Cookie` aren't leaked unless explicitly allowed.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::http::request_from_http;
+///
+/// let request = http::Request::get("https://example.com/users?page=2")
+///     .header("User-Agent", "curl/8.0")
+///     .header("Authorization", "Bearer hunter2")
+///     .body(())
+///     .unwrap();
+///
+/// let request = request_from_http(&request, &["user-agent"]);
+/// assert_eq!(request.method.as_deref(), Some("GET"));
+/// assert_eq!(request.query_string.as_deref(), Some("page=2"));
+/// assert_eq!(request.headers.get("user-agent").map(String::as_str), Some("curl/8.0"));
+/// assert!(!request.headers.contains_key("authorization"));
+/// ```
+pub fn request_from_http<B>(request: &http::Request<B>, allowed_headers: &[&str]) -> Request {
+    build_request(
+        request.method(),
+        request.uri(),
+        request.headers(),
+        allowed_headers,
+    )
+}
+
+/// Same as [`request_from_http`], but builds the [`Request`] context from the [`Parts`] of
+/// a request instead.
+pub fn request_from_parts(parts: &Parts, allowed_headers: &[&str]) -> Request {
+    build_request(&parts.method, &parts.uri, &parts.headers, allowed_headers)
+}
+
+fn build_request(
+    method: &Method,
+    uri: &Uri,
+    headers: &HeaderMap,
+    allowed_headers: &[&str],
+) -> Request {
+    // Requests received by a server usually only carry the path in their URI, so the URL
+    // is reconstructed from the `Host` header if needed.
+    let url = Url::parse(&uri.to_string()).ok().or_else(|| {
+        let host = headers.get(http::header::HOST)?.to_str().ok()?;
+        let path = uri.path_and_query().map(|pq| pq.as_str()).unwrap_or("/");
+
+        Url::parse(&format!("http://{host}{path}")).ok()
+    });
+
+    let headers = headers
+        .iter()
+        .filter(|(name, _)| {
+            allowed_headers
+                .iter()
+                .any(|allowed| name.as_str().eq_ignore_ascii_case(allowed))
+        })
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+        .collect();
+
+    Request {
+        url,
+        method: Some(method.to_string()),
+        query_string: uri.query().map(ToOwned::to_owned),
+        headers,
+        ..Default::default()
+    }
+}
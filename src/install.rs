@@ -0,0 +1,230 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use eyre::InstallError;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the hook that was installed by [`Installer::install`] records breadcrumbs.
+#[cfg_attr(feature = "stable-backtrace", allow(dead_code))]
+static RECORD_BREADCRUMBS: AtomicBool = AtomicBool::new(false);
+
+/// Installs an eyre hook that is set up for Sentry in one call, i.e. at the start of `main`,
+/// right after initializing Sentry. See [`Installer`] on what is set up.
+///
+/// ## Example
+/// ```
+/// # std::env::remove_var("RUST_BACKTRACE");
+/// # std::env::remove_var("RUST_LIB_BACKTRACE");
+/// let _guard = sentry_eyre::install().unwrap();
+/// let event = sentry_eyre::event_from_report(&eyre::eyre!("oh no"));
+///
+/// assert!(event.exception[0].stacktrace.is_some());
+/// ```
+pub fn install() -> Result<InstallGuard, InstallError> {
+    Installer::new().install()
+}
+
+/// Builder to install an eyre hook that is set up for Sentry: it uses [`stable_eyre`]'s
+/// handler if the `stable-backtrace` feature is enabled, or `eyre`'s
+/// [`DefaultHandler`](eyre::DefaultHandler) otherwise, and captures backtraces unless
+/// told not to.
+///
+/// **Feature:** `stable-backtrace` or `std-backtrace` (*disabled by default*)
+#[derive(Debug, Clone)]
+pub struct Installer {
+    capture_backtraces: bool,
+    breadcrumbs: bool,
+}
+
+impl Default for Installer {
+    fn default() -> Self {
+        Installer {
+            capture_backtraces: true,
+            breadcrumbs: false,
+        }
+    }
+}
+
+impl Installer {
+    /// Creates a new [`Installer`], which captures backtraces but doesn't record breadcrumbs.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether reports capture a backtrace if neither `RUST_LIB_BACKTRACE` nor `RUST_BACKTRACE`
+    /// are set, which is the default. The variables still take precedence, but are never
+    /// modified.
+    pub fn capture_backtraces(mut self, capture: bool) -> Self {
+        self.capture_backtraces = capture;
+        self
+    }
+
+    /// Whether to add a breadcrumb to the current hub whenever a report is created, which makes
+    /// errors that were handled show up as context on the next event. Since it isn't known yet
+    /// whether a report will be captured, this includes the report of the event itself.
+    ///
+    /// **Unsupported with `stable-backtrace`:** [`stable_eyre`]'s handler can't be wrapped to
+    /// record breadcrumbs, so this only works with `eyre`'s default handler and is ignored if
+    /// the `stable-backtrace` feature is enabled.
+    ///
+    /// ## Example
+    /// ```
+    /// # #[cfg(not(feature = "stable-backtrace"))]
+    /// # {
+    /// use sentry_eyre::Installer;
+    ///
+    /// let _guard = Installer::new().breadcrumbs(true).install().unwrap();
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let _ = eyre::eyre!("cache miss");
+    ///     sentry_eyre::capture_report(&eyre::eyre!("request failed"));
+    /// });
+    ///
+    /// let breadcrumbs = &events[0].breadcrumbs;
+    /// assert_eq!(breadcrumbs.len(), 2);
+    /// assert_eq!(breadcrumbs[0].message.as_deref(), Some("cache miss"));
+    /// assert_eq!(breadcrumbs[1].message.as_deref(), Some("request failed"));
+    /// # }
+    /// ```
+    pub fn breadcrumbs(mut self, record: bool) -> Self {
+        self.breadcrumbs = record;
+        self
+    }
+
+    /// Installs the hook, which fails if a hook was already installed.
+    pub fn install(self) -> Result<InstallGuard, InstallError> {
+        #[cfg(feature = "stable-backtrace")]
+        stable_eyre::HookBuilder::default()
+            .capture_backtrace_by_default(self.capture_backtraces)
+            .install()
+            .map_err(|report| report.downcast().unwrap_or(InstallError))?;
+
+        #[cfg(not(feature = "stable-backtrace"))]
+        {
+            use crate::breadcrumb::breadcrumb_from_error;
+            use sentry_core::Hub;
+
+            let capture_backtraces = self.capture_backtraces;
+            eyre::set_hook(Box::new(move |err| {
+                if RECORD_BREADCRUMBS.load(Ordering::Relaxed) {
+                    Hub::with_active(|hub| hub.add_breadcrumb(breadcrumb_from_error(err)));
+                }
+
+                Box::new(InstalledHandler::new(err, capture_backtraces))
+            }))?;
+
+            RECORD_BREADCRUMBS.store(self.breadcrumbs, Ordering::Relaxed);
+        }
+
+        Ok(InstallGuard { _priv: () })
+    }
+}
+
+/// The handler that is installed by [`Installer::install`] without the `stable-backtrace`
+/// feature: `eyre`'s [`DefaultHandler`](eyre::DefaultHandler), along with a backtrace that is
+/// captured if the environment doesn't configure capturing backtraces at all, and rendered
+/// like the default handler renders its own.
+#[cfg(not(feature = "stable-backtrace"))]
+pub(crate) struct InstalledHandler {
+    inner: Box<dyn eyre::EyreHandler>,
+    backtrace: Option<std::backtrace::Backtrace>,
+}
+
+#[cfg(not(feature = "stable-backtrace"))]
+impl InstalledHandler {
+    fn new(err: &(dyn std::error::Error + 'static), capture_backtraces: bool) -> Self {
+        let unset = |var| std::env::var_os(var).is_none();
+        let backtrace =
+            (capture_backtraces && unset("RUST_LIB_BACKTRACE") && unset("RUST_BACKTRACE"))
+                .then(std::backtrace::Backtrace::force_capture);
+
+        InstalledHandler {
+            inner: eyre::DefaultHandler::default_with(err),
+            backtrace,
+        }
+    }
+}
+
+#[cfg(not(feature = "stable-backtrace"))]
+impl eyre::EyreHandler for InstalledHandler {
+    fn debug(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        self.inner.debug(error, f)?;
+        if let Some(backtrace) = self.backtrace.as_ref().filter(|_| !f.alternate()) {
+            write!(f, "\n\nStack backtrace:\n{backtrace}")?;
+        }
+
+        Ok(())
+    }
+
+    fn display(
+        &self,
+        error: &(dyn std::error::Error + 'static),
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        self.inner.display(error, f)
+    }
+
+    fn track_caller(&mut self, location: &'static std::panic::Location<'static>) {
+        self.inner.track_caller(location);
+    }
+}
+
+/// Guard that is returned by [`install`]. The hook itself can't be uninstalled, but dropping
+/// the guard stops recording [breadcrumbs](Installer::breadcrumbs).
+#[must_use = "dropping the guard stops recording breadcrumbs"]
+#[derive(Debug)]
+pub struct InstallGuard {
+    _priv: (),
+}
+
+impl Drop for InstallGuard {
+    fn drop(&mut self) {
+        RECORD_BREADCRUMBS.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Installs [`stable_eyre`]'s handler like [`stable_eyre::install`], but captures the error
+/// (if a client is bound) when a handler was already installed, so setup failures don't go
+/// unnoticed like they would with `.unwrap()`-ing in a place where panics are swallowed.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::try_install_and_report;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     assert!(try_install_and_report().is_ok());
+///     assert!(try_install_and_report().is_err());
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+#[cfg(feature = "stable-backtrace")]
+pub fn try_install_and_report() -> Result<(), InstallError> {
+    let Err(report) = stable_eyre::install() else {
+        return Ok(());
+    };
+
+    crate::capture_report(&report);
+    Err(report.downcast().unwrap_or(InstallError))
+}
@@ -0,0 +1,347 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{hooks::BeforeCapture, EventOptions};
+use sentry_core::{
+    protocol::{Event, Level},
+    ClientOptions, Integration,
+};
+use std::{error::Error, sync::Arc};
+
+/// Integration that configures how reports are captured through the client's
+/// [`ClientOptions`], like other Sentry integrations do.
+///
+/// The integration's [`EventOptions`] are used by every capture function that doesn't take
+/// explicit options while the client is bound; they take precedence over the
+/// [default options](crate::set_default_options), but not over [`with_options`](crate::with_options).
+///
+/// The integration doesn't override [`Integration::process_event`]: it only receives the
+/// event, while [`before_capture_report`](Self::before_capture_report) and the emitted logs
+/// need the error that the event was created from, and the attachment has to be added to the
+/// scope before the event is captured. It also runs for every event that the client sends
+/// (i.e. messages or panics), not only for those that were created from reports. Instead, the
+/// capture functions of this crate look the bound integration up when they create and
+/// capture an event.
+///
+/// ## Example
+/// ```
+/// use sentry::ClientOptions;
+/// use sentry_eyre::{capture_report, EventOptions, EyreIntegration};
+///
+/// let options = ClientOptions::new().add_integration(EyreIntegration::new().options(EventOptions {
+///     dist: Some("42".into()),
+///     ..Default::default()
+/// }));
+///
+/// let events = sentry::test::with_captured_events_options(
+///     || {
+///         capture_report(&eyre::eyre!("oh no"));
+///     },
+///     options,
+/// );
+///
+/// assert_eq!(events[0].dist.as_deref(), Some("42"));
+/// assert!(events[0]
+///     .sdk
+///     .as_ref()
+///     .unwrap()
+///     .integrations
+///     .contains(&"eyre".to_string()));
+/// ```
+#[derive(Clone, Default)]
+pub struct EyreIntegration {
+    pub(crate) options: Arc<EventOptions>,
+    pub(crate) before_capture: Option<BeforeCapture>,
+    pub(crate) attach_debug_report: bool,
+    pub(crate) fail_active_span: bool,
+    #[cfg(feature = "logs")]
+    pub(crate) emit_logs: bool,
+    #[cfg(feature = "stable-backtrace")]
+    install_handler: bool,
+}
+
+impl EyreIntegration {
+    /// Creates a new [`EyreIntegration`] with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses the given [`EventOptions`] when creating events from reports. This replaces the
+    /// options that were set by [`level`](Self::level), [`attach_backtraces`](Self::attach_backtraces)
+    /// and [`max_chain_depth`](Self::max_chain_depth) before.
+    pub fn options(mut self, options: EventOptions) -> Self {
+        self.options = Arc::new(options);
+        self
+    }
+
+    /// Sets the default [`Level`] of events, see [`EventOptions::level`].
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry::{protocol::Level, ClientOptions};
+    /// use sentry_eyre::{capture_report, EyreIntegration};
+    ///
+    /// let integration = EyreIntegration::new()
+    ///     .level(Level::Warning)
+    ///     .attach_backtraces(false)
+    ///     .max_chain_depth(1)
+    ///     .before_capture_report(|err, mut event| {
+    ///         let chain = std::iter::successors(Some(err), |&err| err.source());
+    ///         event.tags.insert("chain".into(), chain.count().to_string());
+    ///         Some(event)
+    ///     });
+    ///
+    /// let events = sentry::test::with_captured_events_options(
+    ///     || {
+    ///         let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///             .wrap_err("sync failed")
+    ///             .unwrap_err();
+    ///
+    ///         capture_report(&report);
+    ///     },
+    ///     ClientOptions::new().add_integration(integration),
+    /// );
+    ///
+    /// assert_eq!(events[0].level, Level::Warning);
+    /// assert_eq!(events[0].exception.len(), 2);
+    /// assert_eq!(events[0].exception[0].ty, "TruncatedChain");
+    /// assert!(events[0].exception[1].stacktrace.is_none());
+    /// assert_eq!(events[0].tags["chain"], "2");
+    /// ```
+    pub fn level(mut self, level: Level) -> Self {
+        Arc::make_mut(&mut self.options).level = Some(level);
+        self
+    }
+
+    /// Whether to attach the backtraces of reports to events, which is the default. See
+    /// [`EventOptions::skip_backtraces`].
+    pub fn attach_backtraces(mut self, attach: bool) -> Self {
+        Arc::make_mut(&mut self.options).skip_backtraces = !attach;
+        self
+    }
+
+    /// Limits the exceptions of events to the outermost `depth` links of the chain, see
+    /// [`EventOptions::max_chain_depth`].
+    pub fn max_chain_depth(mut self, depth: usize) -> Self {
+        Arc::make_mut(&mut self.options).max_chain_depth = Some(depth);
+        self
+    }
+
+    /// Registers a callback that runs right before an event that was created from a
+    /// [`Report`](eyre::Report) (or any other error) is captured while the client is bound,
+    /// after the hooks that were registered with [`add_before_capture`](crate::add_before_capture),
+    /// which it receives the same arguments as. Returning [`None`] drops the event.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::ClientOptions;
+    /// use sentry_eyre::{capture_report, EyreIntegration};
+    ///
+    /// let integration = EyreIntegration::new().before_capture_report(|err, mut event| {
+    ///     if err.to_string().contains("customer") {
+    ///         return None;
+    ///     }
+    ///
+    ///     for exc in event.exception.iter_mut() {
+    ///         exc.value = exc.value.take().map(|value| value.replace("/home/noel", "~"));
+    ///     }
+    ///
+    ///     Some(event)
+    /// });
+    ///
+    /// let events = sentry::test::with_captured_events_options(
+    ///     || {
+    ///         capture_report(&eyre::eyre!("failed to read /home/noel/config.toml"));
+    ///         capture_report(&eyre::eyre!("unknown customer 42"));
+    ///     },
+    ///     ClientOptions::new().add_integration(integration),
+    /// );
+    ///
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(
+    ///     events[0].exception[0].value.as_deref(),
+    ///     Some("failed to read ~/config.toml")
+    /// );
+    /// ```
+    pub fn before_capture_report<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static), Event<'static>) -> Option<Event<'static>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.before_capture = Some(Arc::new(callback));
+        self
+    }
+
+    /// Whether to attach the report's `Debug` rendering (i.e. with the sections of
+    /// `color-eyre`) as the `eyre-report.txt` attachment to every event that is created from
+    /// a report while the client is bound. ANSI escape sequences are stripped off.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::{protocol::EnvelopeItem, ClientOptions};
+    /// use sentry_eyre::{capture_report, EyreIntegration};
+    ///
+    /// let envelopes = sentry::test::with_captured_envelopes_options(
+    ///     || {
+    ///         capture_report(&eyre::eyre!("export failed"));
+    ///     },
+    ///     ClientOptions::new().add_integration(EyreIntegration::new().attach_debug_report(true)),
+    /// );
+    ///
+    /// let attachment = envelopes[0]
+    ///     .items()
+    ///     .find_map(|item| match item {
+    ///         EnvelopeItem::Attachment(attachment) => Some(attachment),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(attachment.filename, "eyre-report.txt");
+    /// assert!(String::from_utf8_lossy(&attachment.buffer).starts_with("export failed"));
+    /// ```
+    pub fn attach_debug_report(mut self, attach: bool) -> Self {
+        self.attach_debug_report = attach;
+        self
+    }
+
+    /// Whether to mark the active transaction or span of the current scope as failed whenever
+    /// an event is created from a report while the client is bound: its status is set to
+    /// [`SpanStatus::InternalError`](sentry_core::protocol::SpanStatus::InternalError), the
+    /// report's message is recorded as its `error.message` data, and its trace context is
+    /// attached to the event so that the error shows up within the trace.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::{
+    ///     protocol::{Context, SpanStatus},
+    ///     ClientOptions, TransactionContext,
+    /// };
+    /// use sentry_eyre::{capture_report, EyreIntegration};
+    ///
+    /// let mut txn = None;
+    /// let events = sentry::test::with_captured_events_options(
+    ///     || {
+    ///         let transaction = sentry::start_transaction(TransactionContext::new("job", "task"));
+    ///         let span = transaction.start_child("db", "SELECT 1");
+    ///         sentry::configure_scope(|scope| scope.set_span(Some(span.clone().into())));
+    ///
+    ///         capture_report(&eyre::eyre!("query failed"));
+    ///         txn = Some((span.get_trace_context(), span.get_status()));
+    ///     },
+    ///     ClientOptions {
+    ///         traces_sample_rate: 1.0,
+    ///         ..ClientOptions::new().add_integration(EyreIntegration::new().fail_active_span(true))
+    ///     },
+    /// );
+    ///
+    /// let (span_trace, status) = txn.unwrap();
+    /// let Some(Context::Trace(trace)) = events[0].contexts.get("trace") else {
+    ///     panic!("expected a trace context");
+    /// };
+    ///
+    /// assert_eq!(trace.trace_id, span_trace.trace_id);
+    /// assert_eq!(trace.span_id, span_trace.span_id);
+    /// assert_eq!(status, Some(SpanStatus::InternalError));
+    /// ```
+    pub fn fail_active_span(mut self, fail: bool) -> Self {
+        self.fail_active_span = fail;
+        self
+    }
+
+    /// Whether to emit a structured log (with the report's message as its body) alongside
+    /// every event that is created from a report while the client is bound, so that events
+    /// can be correlated with logs. The log carries the chain of the report as the
+    /// `eyre.chain.{index}` attributes (`0` being the outermost error) and the event's id as
+    /// the `sentry.event_id` attribute.
+    ///
+    /// Logs need to be enabled with [`ClientOptions::enable_logs`](sentry_core::ClientOptions::enable_logs)
+    /// as well; otherwise only the event is sent.
+    ///
+    /// **Feature:** `logs` (*disabled by default*)
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry::{
+    ///     protocol::{EnvelopeItem, ItemContainer, LogLevel},
+    ///     ClientOptions,
+    /// };
+    /// use sentry_eyre::{capture_report, EyreIntegration};
+    ///
+    /// let envelopes = sentry::test::with_captured_envelopes_options(
+    ///     || {
+    ///         let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///             .wrap_err("sync failed")
+    ///             .unwrap_err();
+    ///
+    ///         capture_report(&report);
+    ///     },
+    ///     ClientOptions {
+    ///         enable_logs: true,
+    ///         ..ClientOptions::new().add_integration(EyreIntegration::new().emit_logs(true))
+    ///     },
+    /// );
+    ///
+    /// let logs = envelopes
+    ///     .iter()
+    ///     .flat_map(|envelope| envelope.items())
+    ///     .find_map(|item| match item {
+    ///         EnvelopeItem::ItemContainer(ItemContainer::Logs(logs)) => Some(logs),
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(logs[0].level, LogLevel::Error);
+    /// assert_eq!(logs[0].body, "sync failed");
+    /// assert_eq!(logs[0].attributes["eyre.chain.0"].0, "sync failed");
+    /// assert_eq!(logs[0].attributes["eyre.chain.1"].0, "connection reset");
+    /// assert!(logs[0].attributes.contains_key("sentry.event_id"));
+    /// ```
+    #[cfg(feature = "logs")]
+    pub fn emit_logs(mut self, emit: bool) -> Self {
+        self.emit_logs = emit;
+        self
+    }
+
+    /// Whether to install [`stable_eyre`]'s handler when the integration is set up, so that
+    /// reports carry backtraces. Installing fails silently if a handler was already installed.
+    #[cfg(feature = "stable-backtrace")]
+    pub fn install_handler(mut self, install: bool) -> Self {
+        self.install_handler = install;
+        self
+    }
+}
+
+impl Integration for EyreIntegration {
+    fn name(&self) -> &'static str {
+        "eyre"
+    }
+
+    fn setup(&self, _options: &mut ClientOptions) {
+        #[cfg(feature = "stable-backtrace")]
+        if self.install_handler {
+            let _ = stable_eyre::install();
+        }
+    }
+}
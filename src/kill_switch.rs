@@ -0,0 +1,69 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The environment variable that disables capturing reports when set to `1` or `true`.
+pub const KILL_SWITCH_ENV: &str = "SENTRY_EYRE_DISABLED";
+
+const UNKNOWN: u8 = 0;
+const INACTIVE: u8 = 1;
+const ACTIVE: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(UNKNOWN);
+
+/// Checks if the kill switch is active, which turns every capture function into a no-op. The
+/// [`KILL_SWITCH_ENV`] environment variable is only read once and then cached; use
+/// [`reload_kill_switch`] to read it again.
+pub fn is_kill_switch_active() -> bool {
+    match STATE.load(Ordering::Relaxed) {
+        UNKNOWN => reload_kill_switch(),
+        state => state == ACTIVE,
+    }
+}
+
+/// Reads the [`KILL_SWITCH_ENV`] environment variable again, and returns whether the kill
+/// switch is active now. This allows toggling the kill switch without restarting the process.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, reload_kill_switch, KILL_SWITCH_ENV};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     std::env::set_var(KILL_SWITCH_ENV, "1");
+///     assert!(reload_kill_switch());
+///     assert!(capture_report(&eyre::eyre!("not captured")).is_nil());
+///
+///     std::env::remove_var(KILL_SWITCH_ENV);
+///     assert!(!reload_kill_switch());
+///     assert!(!capture_report(&eyre::eyre!("captured")).is_nil());
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn reload_kill_switch() -> bool {
+    let active = std::env::var(KILL_SWITCH_ENV)
+        .map(|value| value == "1" || value.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    STATE.store(if active { ACTIVE } else { INACTIVE }, Ordering::Relaxed);
+    active
+}
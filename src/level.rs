@@ -0,0 +1,142 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{hooks, should_capture, CaptureReportBuilder};
+use eyre::Report;
+use sentry_core::{protocol::Level, types::Uuid, Hub};
+
+/// Converts an HTTP status code into a Sentry [`Level`]: server errors (`5xx`) are errors,
+/// client errors (`4xx`) are warnings, and everything else is informational.
+pub fn level_from_status(status: u16) -> Level {
+    match status {
+        500..=599 => Level::Error,
+        400..=499 => Level::Warning,
+        _ => Level::Info,
+    }
+}
+
+/// Captures a [`Report`] that occurred while handling an HTTP request with the given
+/// response status, with the [`Level`] that corresponds to its class (see
+/// [`level_from_status`]) and the status tagged as `http.status_code`.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_report_for_status;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_for_status(&eyre::eyre!("database is down"), 500);
+///     capture_report_for_status(&eyre::eyre!("user not found"), 404);
+/// });
+///
+/// assert_eq!(events[0].level, Level::Error);
+/// assert_eq!(events[0].tags["http.status_code"], "500");
+/// assert_eq!(events[1].level, Level::Warning);
+/// assert_eq!(events[1].tags["http.status_code"], "404");
+/// ```
+pub fn capture_report_for_status(report: &Report, status: u16) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = CaptureReportBuilder::new(report)
+            .level(level_from_status(status))
+            .build();
+
+        event
+            .tags
+            .insert("http.status_code".into(), status.to_string());
+
+        hooks::capture_event(hub, report, event)
+    })
+}
+
+/// Converts a [`log::Level`] into a Sentry [`Level`].
+///
+/// **Feature:** `log` (*disabled by default*)
+#[cfg(feature = "log")]
+pub fn level_from_log(level: log::Level) -> Level {
+    match level {
+        log::Level::Error => Level::Error,
+        log::Level::Warn => Level::Warning,
+        log::Level::Info => Level::Info,
+        log::Level::Debug | log::Level::Trace => Level::Debug,
+    }
+}
+
+/// Captures a [`Report`] with the Sentry [`Level`] that corresponds to the given [`log::Level`].
+///
+/// **Feature:** `log` (*disabled by default*)
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_report_from_log_level;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_from_log_level(&eyre::eyre!("disk almost full"), log::Level::Warn);
+/// });
+///
+/// assert_eq!(events[0].level, Level::Warning);
+/// ```
+#[cfg(feature = "log")]
+pub fn capture_report_from_log_level(report: &Report, level: log::Level) -> Uuid {
+    CaptureReportBuilder::new(report)
+        .level(level_from_log(level))
+        .capture()
+}
+
+/// Converts a [`tracing_core::Level`] into a Sentry [`Level`].
+///
+/// **Feature:** `tracing` (*disabled by default*)
+#[cfg(feature = "tracing")]
+pub fn level_from_tracing(level: &tracing_core::Level) -> Level {
+    match *level {
+        tracing_core::Level::ERROR => Level::Error,
+        tracing_core::Level::WARN => Level::Warning,
+        tracing_core::Level::INFO => Level::Info,
+        _ => Level::Debug,
+    }
+}
+
+/// Captures a [`Report`] with the Sentry [`Level`] that corresponds to the given
+/// [`tracing_core::Level`].
+///
+/// **Feature:** `tracing` (*disabled by default*)
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_report_from_tracing_level;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_from_tracing_level(&eyre::eyre!("cache miss"), &tracing_core::Level::INFO);
+/// });
+///
+/// assert_eq!(events[0].level, Level::Info);
+/// ```
+#[cfg(feature = "tracing")]
+pub fn capture_report_from_tracing_level(report: &Report, level: &tracing_core::Level) -> Uuid {
+    CaptureReportBuilder::new(report)
+        .level(level_from_tracing(level))
+        .capture()
+}
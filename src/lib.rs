@@ -36,22 +36,1096 @@
 //!     capture_report(&e);
 //! }
 //! ```
+//!
+//! ## Features
+//! - `stable-backtrace`: attaches the backtrace captured by [`stable-eyre`](https://docs.rs/stable-eyre)'s
+//!   handler to the event. This requires `stable_eyre::install()` (or `try_install_and_report()`) to be
+//!   called and `RUST_BACKTRACE=1`.
+//! - `std-backtrace`: attaches the [`std::backtrace::Backtrace`] captured by `eyre`'s default handler
+//!   to the event, which requires `RUST_BACKTRACE=1` (or `RUST_LIB_BACKTRACE=1`) but no other handler.
+//!   This also allows attaching the backtrace of where a report was captured, i.e. via
+//!   `capture_report_with_capture_site`.
+//! - `color-eyre`: attaches the backtrace captured by [`color-eyre`](https://docs.rs/color-eyre)'s handler
+//!   to the event, along with its `SpanTrace` as breadcrumbs (in the `span` category) and the `trace_spans`
+//!   context, and its notes, warnings and suggestions as the `color-eyre` context. This requires `color_eyre::install()` to be called, and
+//!   a `tracing_error::ErrorLayer` to be installed for the `SpanTrace` to be captured.
+//! - `http`: exposes the `http` module with helpers to build a request context from [`http`](https://docs.rs/http)
+//!   types, which can be attached with [`CaptureReportBuilder::request`].
+//! - `log`: adds [`log`](https://docs.rs/log) level conversions, i.e. `capture_report_from_log_level`.
+//! - `logs`: allows emitting a Sentry structured log alongside the event, i.e. via `capture_report_and_log`
+//!   or for every event with `EyreIntegration::emit_logs`.
+//! - `miette`: allows capturing [`miette`](https://docs.rs/miette) reports along with their diagnostic
+//!   metadata, i.e. via `capture_diagnostic`.
+//! - `modules`: allows attaching the versions of the crates in the dependency graph (from `Cargo.lock`)
+//!   to events via `EventOptions::include_modules`.
+//! - `opentelemetry`: allows capturing reports with the trace context of the current
+//!   [OpenTelemetry](https://docs.rs/opentelemetry) span via `capture_report_otel`.
+//! - `process-stats`: allows attaching the process' uptime and memory usage to events via
+//!   `EventOptions::include_process_stats`.
+//! - `tokio`: tags events with the ID of the [`tokio`](https://docs.rs/tokio) task (`tokio.task_id`) they
+//!   were created in, if any.
+//! - `tower`: exposes the `tower` module with a [`tower`](https://docs.rs/tower) layer that captures the
+//!   reports of fallible services along with the request context. This implies `http`.
+//! - `tracing`: adds [`tracing`](https://docs.rs/tracing) level conversions, i.e. `capture_report_from_tracing_level`,
+//!   and attaches the [`SpanTrace`](https://docs.rs/tracing-error) of reports (i.e. from a `TracedError` in the chain
+//!   or `EventOptions::extract_span_trace`) as breadcrumbs and the `trace_spans` context.
+//! - `test-util`: exposes the `test` module with utilities for testing how reports are captured.
+//!
+//! With either of the backtrace features, `sentry_eyre::install()` installs an eyre hook that
+//! captures backtraces (and optionally records breadcrumbs), so no further setup is needed.
+//!
+//! ```
+//! # #[cfg(feature = "stable-backtrace")]
+//! # {
+//! std::env::set_var("RUST_BACKTRACE", "1");
+//! stable_eyre::install().unwrap();
+//!
+//! let event = sentry_eyre::event_from_report(&eyre::eyre!("this method has failed."));
+//! assert!(event.exception.last().unwrap().stacktrace.is_some());
+//! # }
+//! ```
+//!
+//! ```
+//! # #[cfg(feature = "std-backtrace")]
+//! # {
+//! // No handler needs to be installed, `eyre`'s default one captures the backtrace.
+//! std::env::set_var("RUST_BACKTRACE", "1");
+//!
+//! let event = sentry_eyre::event_from_report(&eyre::eyre!("this method has failed."));
+//! assert!(event.exception.last().unwrap().stacktrace.is_some());
+//! # }
+//! ```
+//!
+//! ```
+//! # #[cfg(feature = "color-eyre")]
+//! # {
+//! use color_eyre::Section;
+//! use tracing_subscriber::prelude::*;
+//!
+//! std::env::set_var("RUST_BACKTRACE", "1");
+//! tracing_subscriber::registry()
+//!     .with(tracing_error::ErrorLayer::default())
+//!     .init();
+//! color_eyre::install().unwrap();
+//!
+//! let report = tracing::info_span!("handle_request", id = 42).in_scope(|| {
+//!     eyre::eyre!("this method has failed.").suggestion("try again later")
+//! });
+//!
+//! let event = sentry_eyre::event_from_report(&report);
+//! assert!(event.exception.last().unwrap().stacktrace.is_some());
+//!
+//! let span = &event.breadcrumbs[0];
+//! assert_eq!(span.category.as_deref(), Some("span"));
+//! assert!(span.message.as_deref().unwrap().ends_with("handle_request"));
+//! assert_eq!(span.data["fields"], "id=42");
+//!
+//! let sentry::protocol::Context::Other(sections) = &event.contexts["color-eyre"] else {
+//!     unreachable!();
+//! };
+//!
+//! assert_eq!(sections["suggestions"][0], "try again later");
+//! # }
+//! ```
+
+mod aggregate;
+mod background;
+mod backtrace;
+mod breadcrumb;
+mod builder;
+#[cfg(feature = "std-backtrace")]
+mod capture_site;
+#[cfg(feature = "color-eyre")]
+mod color;
+mod context;
+mod convert;
+mod dedup;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod feedback;
+mod fingerprint;
+mod future;
+mod handle;
+mod hooks;
+#[cfg(any(feature = "stable-backtrace", feature = "std-backtrace"))]
+mod install;
+mod integration;
+mod kill_switch;
+mod level;
+#[cfg(feature = "logs")]
+mod logs;
+mod mapper;
+#[cfg(feature = "modules")]
+mod modules;
+mod nested;
+mod options;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+mod performance;
+#[cfg(feature = "process-stats")]
+mod process;
+mod reference;
+mod result;
+mod sampling;
+mod scope;
+mod sink;
+mod source_context;
+#[cfg(any(feature = "tracing", feature = "color-eyre"))]
+mod span_trace;
+mod split;
+mod strict;
+mod suppress;
+
+#[cfg(feature = "http")]
+pub mod http;
+
+pub mod panic;
+
+#[cfg(feature = "test-util")]
+pub mod test;
+
+#[cfg(feature = "tower")]
+pub mod tower;
+
+pub use aggregate::*;
+pub use background::*;
+pub use breadcrumb::*;
+pub use builder::*;
+#[cfg(feature = "std-backtrace")]
+pub use capture_site::*;
+pub use context::{pop_context, push_context, with_context};
+pub use convert::*;
+pub use dedup::*;
+#[cfg(feature = "miette")]
+pub use diagnostic::*;
+pub use feedback::*;
+pub use fingerprint::*;
+pub use future::*;
+pub use handle::*;
+pub use hooks::{add_before_capture, clear_before_capture};
+#[cfg(any(feature = "stable-backtrace", feature = "std-backtrace"))]
+pub use install::*;
+pub use integration::*;
+pub use kill_switch::*;
+pub use level::*;
+#[cfg(feature = "logs")]
+pub use logs::*;
+pub use mapper::{clear_report_mappers, ReportMapper};
+pub use nested::*;
+pub use options::*;
+#[cfg(feature = "opentelemetry")]
+pub use otel::*;
+pub use performance::*;
+pub use reference::*;
+pub use result::*;
+pub use sampling::*;
+pub use scope::*;
+pub use sink::*;
+#[cfg(any(feature = "tracing", feature = "color-eyre"))]
+pub use span_trace::attach_span_trace;
+pub use split::*;
+pub use strict::*;
+pub use suppress::{add_suppressed_type, remove_suppressed_type};
+
+#[doc(hidden)]
+pub use eyre as __eyre;
 
+use backtrace::ReportBacktrace;
 use eyre::Report;
-use sentry_core::{event_from_error, protocol::Event, types::Uuid, Hub};
-use std::error::Error;
+use sentry_core::{
+    event_from_error,
+    protocol::{Event, Exception, Level, LogEntry, Mechanism, Value},
+    types::Uuid,
+    Hub, Scope,
+};
+use std::{
+    error::Error,
+    fmt::{self, Write},
+    time::{Duration, Instant},
+};
 
 /// Captures a [`Report`] and sends it to Sentry. Refer to the top-level
 /// module documentation on how to use this method.
+///
+/// When no client is bound (or capturing is otherwise disabled), this returns a nil
+/// [`Uuid`] right away without creating the event, so it's cheap to call in hot paths. If
+/// a [`ReportSink`] was registered with [`set_report_sink`], the event is stored with it
+/// instead.
 pub fn capture_report(report: &Report) -> Uuid {
-    Hub::with_active(|hub| hub.capture_report(report))
+    Hub::with_active(|hub| Some(hub.capture_report(report)))
+        .unwrap_or_else(|| Hub::with(|hub| sink::spool_offline_report(hub, report)))
+}
+
+/// Creates a [`Report`] with [`eyre::eyre!`] and captures it right away with
+/// [`capture_report`], returning the event's [`Uuid`]. This accepts the same arguments as
+/// `eyre!` and is meant for quick instrumentation where the error isn't propagated.
+///
+/// ## Example
+/// ```
+/// let events = sentry::test::with_captured_events(|| {
+///     let shard = 3;
+///     let timeout = 30;
+///
+///     sentry_eyre::report!("cache miss");
+///     sentry_eyre::report!("shard {} is unavailable", shard);
+///     sentry_eyre::report!("timed out after {timeout}s");
+///     sentry_eyre::report!(std::io::Error::other("disk is full"));
+/// });
+///
+/// let values = events
+///     .iter()
+///     .map(|event| event.exception[0].value.as_deref().unwrap())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     values,
+///     ["cache miss", "shard 3 is unavailable", "timed out after 30s", "disk is full"]
+/// );
+/// ```
+#[macro_export]
+macro_rules! report {
+    ($($arg:tt)*) => {
+        $crate::capture_report(&$crate::__eyre::eyre!($($arg)*))
+    };
+}
+
+/// Diagnostics about a captured [`Report`], returned by [`capture_report_detailed`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureResult {
+    /// The ID of the captured event, which is nil if nothing was captured.
+    pub event_id: Uuid,
+
+    /// Whether a stacktrace was attached to any of the event's exceptions.
+    pub had_backtrace: bool,
+
+    /// The amount of exceptions the event held.
+    pub exception_count: usize,
+}
+
+/// Same as [`capture_report`], but also reports diagnostics about the event that
+/// was sent, like if a backtrace made it onto the event.
+///
+/// ## Example
+/// ```
+/// use eyre::WrapErr;
+/// use sentry_eyre::capture_report_detailed;
+///
+/// # std::env::set_var("RUST_BACKTRACE", "1");
+/// # #[cfg(feature = "stable-backtrace")]
+/// # stable_eyre::install().unwrap();
+/// sentry::test::with_captured_events(|| {
+///     let report = Err::<(), _>(eyre::eyre!("inner")).wrap_err("outer").unwrap_err();
+///     let result = capture_report_detailed(&report);
+///
+///     assert!(!result.event_id.is_nil());
+///     assert_eq!(result.exception_count, 2);
+///     assert_eq!(
+///         result.had_backtrace,
+///         cfg!(any(feature = "stable-backtrace", feature = "std-backtrace"))
+///     );
+/// });
+/// ```
+pub fn capture_report_detailed(report: &Report) -> CaptureResult {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return CaptureResult::default();
+        }
+
+        let event = event_from_report(report);
+        let had_backtrace = event.exception.iter().any(|exc| exc.stacktrace.is_some());
+        let exception_count = event.exception.len();
+
+        CaptureResult {
+            event_id: hooks::capture_event(hub, report, event),
+            had_backtrace,
+            exception_count,
+        }
+    })
+}
+
+/// Captures a [`Report`] with the [`Level::Fatal`] level and immediately flushes the
+/// client, waiting up to `timeout` for the event to be delivered. This is meant for
+/// the "we're about to crash" case; returns `true` if the event was delivered.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_fatal;
+/// use std::time::Duration;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::eyre!("the world is ending");
+///     assert!(capture_fatal(&report, Duration::from_secs(2)));
+/// });
+///
+/// assert_eq!(events[0].level, Level::Fatal);
+/// ```
+pub fn capture_fatal(report: &Report, timeout: Duration) -> bool {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return false;
+        }
+
+        let mut event = event_from_report(report);
+        event.level = Level::Fatal;
+
+        hooks::capture_event(hub, report, event);
+        hub.client()
+            .map(|client| client.flush(Some(timeout)))
+            .unwrap_or(false)
+    })
+}
+
+/// Captures a [`Report`] with the given [`Level`] rather than [`Level::Error`], i.e. for
+/// errors that are recovered from. The level takes precedence over [`EventOptions::level`]
+/// and [`EventOptions::infer_level`].
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_report_with_level;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_with_level(&eyre::eyre!("retrying with a fallback"), Level::Warning);
+/// });
+///
+/// assert_eq!(events[0].level, Level::Warning);
+/// assert_eq!(events[0].exception.len(), 1);
+/// ```
+pub fn capture_report_with_level(report: &Report, level: Level) -> Uuid {
+    Hub::with_active(|hub| hub.capture_report_with_level(report, level))
+}
+
+/// Captures a [`Report`] with the given tags and extra data, without having to configure a
+/// scope. Non-string tag values are converted into their JSON representation. Tags and extra
+/// data of the current scope with the same key take precedence.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_with;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     sentry::configure_scope(|scope| scope.set_tag("region", "us-east-1"));
+///     capture_report_with(
+///         &eyre::eyre!("upload failed"),
+///         [("bucket", "avatars"), ("region", "eu-west-1")],
+///         [("attempt", 3)],
+///     );
+/// });
+///
+/// assert_eq!(events[0].tags["bucket"], "avatars");
+/// assert_eq!(events[0].tags["region"], "us-east-1");
+/// assert_eq!(events[0].extra["attempt"], 3);
+/// ```
+pub fn capture_report_with<'k, T, TV, E, EV>(report: &Report, tags: T, extra: E) -> Uuid
+where
+    T: IntoIterator<Item = (&'k str, TV)>,
+    TV: Into<Value>,
+    E: IntoIterator<Item = (&'k str, EV)>,
+    EV: Into<Value>,
+{
+    Hub::with_active(|hub| hub.capture_report_with(report, tags, extra))
+}
+
+/// Captures a [`Report`] as a message event with the given [`Level`], rather than as an
+/// exception. This is meant for informational captures that don't warrant an issue.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::capture_report_message;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_message(&eyre::eyre!("cache was rebuilt"), Level::Info);
+/// });
+///
+/// assert_eq!(events[0].message.as_deref(), Some("cache was rebuilt"));
+/// assert!(events[0].exception.is_empty());
+/// ```
+pub fn capture_report_message(report: &Report, level: Level) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        // The outermost exception's value is the report's message, which already went through
+        // the options (i.e. `strip_ansi`).
+        let mut event = event_from_report(report);
+        event.message = event.exception.values.pop().and_then(|exc| exc.value);
+        event.exception = Default::default();
+        event.level = level;
+
+        hooks::capture_event(hub, report, event)
+    })
+}
+
+/// Captures a [`Report`] that is related to a previously captured event, i.e. when several
+/// sub-operations of a request fail. The parent's id is recorded as the `parent_event_id`
+/// extra, which makes the events navigable via saved searches.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, capture_report_linked};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let parent = capture_report(&eyre::eyre!("request failed"));
+///     capture_report_linked(&eyre::eyre!("cache write failed"), parent);
+/// });
+///
+/// assert_eq!(
+///     events[1].extra["parent_event_id"],
+///     events[0].event_id.simple().to_string()
+/// );
+/// ```
+pub fn capture_report_linked(report: &Report, parent_event_id: Uuid) -> Uuid {
+    CaptureReportBuilder::new(report)
+        .extra("parent_event_id", parent_event_id.simple().to_string())
+        .capture()
+}
+
+/// Captures a [`Report`] on the `primary` [`Hub`], or on the `fallback` hub if the primary one
+/// has no bound (or a disabled) client, i.e. to report critical errors to a self-hosted
+/// instance when the primary one isn't configured. Returns the id of the event from whichever
+/// hub it was captured on.
+///
+/// Reports that are dropped by the primary hub (i.e. by a hook or suppression) aren't sent
+/// to the fallback.
+///
+/// ## Example
+/// ```
+/// use sentry::{Hub, Scope};
+/// use sentry_eyre::capture_report_with_fallback;
+/// use std::sync::Arc;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let primary = Hub::new(None, Arc::new(Scope::default()));
+///     let id = capture_report_with_fallback(
+///         &eyre::eyre!("ledger is out of sync"),
+///         &primary,
+///         &Hub::current(),
+///     );
+///
+///     assert!(!id.is_nil());
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].exception[0].value.as_deref(), Some("ledger is out of sync"));
+/// ```
+pub fn capture_report_with_fallback(report: &Report, primary: &Hub, fallback: &Hub) -> Uuid {
+    let usable = primary.client().is_some_and(|client| client.is_enabled());
+
+    if usable {
+        primary.capture_report(report)
+    } else {
+        fallback.capture_report(report)
+    }
+}
+
+/// Captures any boxed [`Error`] (i.e. the ones returned by libraries that don't use `eyre`)
+/// as an event, walking its [`source`](Error::source) chain like [`capture_report`] does. The
+/// event goes through the [`before_capture`](add_before_capture) hooks as well.
+///
+/// A [`Report`] that is converted into a boxed error loses its handler, so its backtrace
+/// can't be recovered. Wrap it in a [`NestedReport`] before boxing it instead: those are
+/// detected and captured as the wrapped report, with its backtrace.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_boxed_error, NestedReport};
+/// use std::{error::Error, io};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let plain: Box<dyn Error + Send + Sync> =
+///         Box::new(io::Error::new(io::ErrorKind::Other, "disk full"));
+///     capture_boxed_error(&*plain);
+///
+///     let report = eyre::eyre!("connection reset").wrap_err("sync failed");
+///     let boxed: Box<dyn Error + Send + Sync> = Box::new(NestedReport::from(report));
+///     capture_boxed_error(&*boxed);
+/// });
+///
+/// assert_eq!(events[0].exception[0].value.as_deref(), Some("disk full"));
+/// assert_eq!(events[1].exception.len(), 2);
+/// assert_eq!(events[1].exception[1].value.as_deref(), Some("sync failed"));
+/// ```
+pub fn capture_boxed_error(err: &(dyn Error + Send + Sync + 'static)) -> Uuid {
+    Hub::with_active(|hub| {
+        if let Some(nested) = err.downcast_ref::<NestedReport>() {
+            return hub.capture_report(nested);
+        }
+
+        if !should_capture_error(hub, err) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_error(err);
+        attach_mechanisms(&mut event);
+        attach_nested_stacktraces(
+            &mut event,
+            std::iter::successors(Some(err as &dyn Error), |&err| err.source()),
+        );
+        apply_options(&mut event, &options::current_options());
+        mapper::apply(err, &mut event);
+
+        hooks::capture_event(hub, err as &(dyn Error + 'static), event)
+    })
 }
 
 /// Utility function to represent a Sentry [`Event`] from a [`Report`]. This shouldn't
 /// be consumed directly unless you want access to the created [`Event`] from a [`Report`].
+///
+/// The event is customized with the current thread's [`with_options`] override or the
+/// [default options](set_default_options), if any.
+///
+/// Every link of the report's chain is represented as its own exception, ordered from the
+/// innermost cause to the outermost error, with a handled `eyre` mechanism that holds the
+/// link's index in the chain (`0` being the outermost error) as `chain_index`. The messages
+/// of the contexts added with [`wrap_err`](eyre::WrapErr::wrap_err) are attached as the
+/// `eyre.context` extra (outermost first), and the outermost one as the event's log entry.
+/// Other errors in the chain aren't contexts, even if they wrap a source.
+///
+/// ## Example
+/// ```
+/// use eyre::WrapErr;
+/// use sentry_eyre::event_from_report;
+///
+/// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+///     .wrap_err("query failed")
+///     .unwrap_err();
+///
+/// let event = event_from_report(&report);
+/// let mechanisms = event
+///     .exception
+///     .iter()
+///     .map(|exc| exc.mechanism.as_ref().unwrap())
+///     .collect::<Vec<_>>();
+///
+/// assert!(mechanisms.iter().all(|m| m.ty == "eyre" && m.handled == Some(true)));
+/// assert_eq!(mechanisms[0].data["chain_index"], 1);
+/// assert_eq!(mechanisms[1].data["chain_index"], 0);
+///
+/// assert_eq!(event.logentry.unwrap().message, "query failed");
+/// assert_eq!(event.extra["eyre.context"], serde_json::json!(["query failed"]));
+///
+/// let report = Err::<(), _>(std::io::Error::other("connection reset"))
+///     .wrap_err("query failed")
+///     .wrap_err_with(|| format!("loading user {}", 42))
+///     .unwrap_err();
+///
+/// let event = event_from_report(&report);
+/// assert_eq!(event.logentry.unwrap().message, "loading user 42");
+/// assert_eq!(
+///     event.extra["eyre.context"],
+///     serde_json::json!(["loading user 42", "query failed"])
+/// );
+///
+/// let event = event_from_report(&eyre::eyre!("connection reset"));
+/// assert!(event.logentry.is_none());
+/// assert!(!event.extra.contains_key("eyre.context"));
+///
+/// #[derive(Debug)]
+/// struct QueryError(std::io::Error);
+///
+/// impl std::fmt::Display for QueryError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         f.write_str("query failed")
+///     }
+/// }
+///
+/// impl std::error::Error for QueryError {
+///     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// let err = QueryError(std::io::Error::other("connection reset"));
+/// let event = event_from_report(&eyre::Report::new(err));
+/// assert!(event.logentry.is_none());
+/// assert!(!event.extra.contains_key("eyre.context"));
+///
+/// let err = QueryError(std::io::Error::other("connection reset"));
+/// let event = event_from_report(&eyre::Report::new(err).wrap_err("can't load \"users\""));
+/// assert_eq!(event.extra["eyre.context"], serde_json::json!(["can't load \"users\""]));
+/// ```
 pub fn event_from_report(report: &Report) -> Event<'static> {
+    event_from_report_with_options(report, &options::current_options())
+}
+
+/// Same as [`event_from_report`], but customizes the created [`Event`] with the
+/// given [`EventOptions`].
+///
+/// ## Example
+/// ```
+/// use eyre::EyreHandler;
+/// use sentry_eyre::{event_from_report_with_options, EventOptions};
+/// use std::{collections::HashMap, error::Error, fmt, sync::Arc};
+///
+/// struct TenantHandler {
+///     tenant: &'static str,
+/// }
+///
+/// impl EyreHandler for TenantHandler {
+///     fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "[{}] {error}", self.tenant)
+///     }
+/// }
+///
+/// eyre::set_hook(Box::new(|_| Box::new(TenantHandler { tenant: "acme" }))).unwrap();
+///
+/// let options = EventOptions {
+///     extract_handler_context: Some(Arc::new(|handler| {
+///         handler
+///             .downcast_ref::<TenantHandler>()
+///             .map(|handler| HashMap::from([("tenant".into(), handler.tenant.into())]))
+///             .unwrap_or_default()
+///     })),
+///     ..Default::default()
+/// };
+///
+/// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+/// assert_eq!(event.extra["tenant"], "acme");
+/// ```
+pub fn event_from_report_with_options(report: &Report, options: &EventOptions) -> Event<'static> {
+    event_from_report_extended(report, options, |_| {})
+}
+
+/// Same as [`event_from_report_with_options`], but calls `extend` with the event before the
+/// options are applied, i.e. to add exceptions that the options should apply to as well.
+pub(crate) fn event_from_report_extended(
+    report: &Report,
+    options: &EventOptions,
+    extend: impl FnOnce(&mut Event<'static>),
+) -> Event<'static> {
+    let started = options.record_capture_timing.then(Instant::now);
+    let mut event = unprocessed_event_from_report(report, options);
+    extend(&mut event);
+    apply_options(&mut event, options);
+    if options.infer_level && mapper::is_expected(report.as_ref()) {
+        event.level = Level::Warning;
+    }
+
+    mapper::apply(report.as_ref(), &mut event);
+
+    if let Some(started) = started {
+        let elapsed = started.elapsed().as_secs_f64() * 1000.0;
+        event
+            .extra
+            .insert("capture_build_ms".into(), elapsed.into());
+    }
+
+    event
+}
+
+/// Creates the event from the report's chain along with its backtraces, without applying the
+/// [`EventOptions`] that don't depend on the report itself.
+pub(crate) fn unprocessed_event_from_report(
+    report: &Report,
+    options: &EventOptions,
+) -> Event<'static> {
+    let mut event = event_from_report_handler(report, options);
+    if !options.skip_backtraces {
+        attach_backtrace(&mut event, backtrace::backtrace_from_report(report));
+        attach_nested_stacktraces(&mut event, report.chain());
+    }
+
+    event
+}
+
+/// Creates the event from the report's chain and applies the [`EventOptions`] that depend on
+/// the report itself (i.e. its handler), but doesn't attach any backtraces.
+pub(crate) fn event_from_report_handler(report: &Report, options: &EventOptions) -> Event<'static> {
     let err: &dyn Error = report.as_ref();
-    event_from_error(err)
+    let mut event = event_from_error(err);
+    attach_mechanisms(&mut event);
+    attach_context_messages(report, &mut event);
+
+    #[cfg(any(feature = "tracing", feature = "color-eyre"))]
+    if let Some(span_trace) = span_trace::span_trace_from_report(report, options) {
+        attach_span_trace(&mut event, &span_trace);
+    }
+
+    #[cfg(feature = "color-eyre")]
+    color::attach_sections(report, &mut event);
+
+    if let Some(extract) = &options.extract_handler_context {
+        event.extra.extend(extract(report.handler()));
+    }
+
+    if options.include_formatted_chain {
+        event
+            .extra
+            .insert("error_chain".into(), format!("{report:#}").into());
+    }
+
+    if options.simplify_string_errors {
+        // Exceptions are ordered from innermost to outermost, while the chain goes the other
+        // way around.
+        for (exc, link) in event.exception.iter_mut().rev().zip(report.chain()) {
+            if is_message_error(link) {
+                exc.ty = "Error".into();
+            }
+        }
+    }
+
+    event
+}
+
+/// Checks if the given error only carries a message, which is the case if its `Debug` output
+/// is the same as its message (quoted or not).
+fn is_message_error(err: &dyn Error) -> bool {
+    let value = err.to_string();
+    let debug = format!("{err:?}");
+
+    debug == value || debug == format!("{value:?}")
+}
+
+/// Attaches the messages of the contexts that were added to the report with
+/// [`wrap_err`](eyre::WrapErr::wrap_err) (outermost first) as the `eyre.context` extra, and
+/// the outermost one as the event's log entry so it ends up as the issue's title.
+fn attach_context_messages(report: &Report, event: &mut Event<'static>) {
+    let contexts = report
+        .chain()
+        .take_while(|link| is_context_error(*link))
+        .map(|link| link.to_string())
+        .collect::<Vec<_>>();
+
+    let Some(outermost) = contexts.first() else {
+        return;
+    };
+
+    event.logentry = Some(LogEntry {
+        message: outermost.clone(),
+        params: Vec::new(),
+    });
+
+    event.extra.insert("eyre.context".into(), contexts.into());
+}
+
+/// Checks if the given error is a context, i.e. one that was added with
+/// [`wrap_err`](eyre::WrapErr::wrap_err). `eyre` doesn't expose its context type, so this
+/// checks for its shape instead: it wraps a source and is rendered as
+/// `Error { msg: "<message>", source: .. }` with its own message. Only that prefix is
+/// rendered, since the source could render a whole backtrace.
+fn is_context_error(err: &dyn Error) -> bool {
+    if err.source().is_none() {
+        return false;
+    }
+
+    let expected = format!(
+        "Error {{ msg: \"{}\", source: ",
+        err.to_string().escape_debug()
+    );
+
+    let mut prefix = PrefixMatcher {
+        rest: &expected,
+        matched: true,
+    };
+
+    // The matcher stops the rendering with an error as soon as the outcome is known.
+    let _ = write!(prefix, "{err:?}");
+    prefix.matched && prefix.rest.is_empty()
+}
+
+/// [`fmt::Write`] implementation that checks if the written output starts with the expected
+/// prefix, failing as soon as it's known whether it does.
+struct PrefixMatcher<'a> {
+    rest: &'a str,
+    matched: bool,
+}
+
+impl fmt::Write for PrefixMatcher<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let len = s.len().min(self.rest.len());
+        if s.as_bytes()[..len] != self.rest.as_bytes()[..len] {
+            self.matched = false;
+            return Err(fmt::Error);
+        }
+
+        self.rest = &self.rest[len..];
+        if self.rest.is_empty() {
+            return Err(fmt::Error);
+        }
+
+        Ok(())
+    }
+}
+
+/// Attaches the `eyre` mechanism to every exception of the event, with the index of its link
+/// in the chain (`0` being the outermost error) as the `chain_index` data. This lets Sentry
+/// display the exceptions as a chain of handled errors.
+pub(crate) fn attach_mechanisms(event: &mut Event<'static>) {
+    let len = event.exception.len();
+    for (idx, exc) in event.exception.iter_mut().enumerate() {
+        let mechanism = exc.mechanism.get_or_insert_with(|| Mechanism {
+            ty: "eyre".into(),
+            handled: Some(true),
+            ..Default::default()
+        });
+
+        // Exceptions are ordered from innermost to outermost, while the chain goes the other
+        // way around.
+        mechanism
+            .data
+            .insert("chain_index".into(), (len - 1 - idx).into());
+    }
+}
+
+/// Attaches the report's backtrace to the outermost exception, or as the `raw_backtrace`
+/// extra if it couldn't be parsed.
+pub(crate) fn attach_backtrace(event: &mut Event<'static>, backtrace: Option<ReportBacktrace>) {
+    match backtrace {
+        Some(ReportBacktrace::Parsed(stacktrace)) => {
+            if let Some(exc) = event.exception.last_mut() {
+                exc.stacktrace = Some(stacktrace);
+            }
+        }
+
+        Some(ReportBacktrace::Raw(raw)) => {
+            event.extra.insert("raw_backtrace".into(), raw.into());
+        }
+
+        None => {}
+    }
+}
+
+/// Attaches the backtraces of all [`NestedReport`]s in the chain (except for the first link)
+/// to their corresponding exception.
+pub(crate) fn attach_nested_stacktraces<'a>(
+    event: &mut Event<'static>,
+    chain: impl Iterator<Item = &'a (dyn Error + 'static)>,
+) {
+    // Exceptions are ordered from innermost to outermost, while the chain goes the other way
+    // around. Nested reports carry their own backtrace, which is more accurate for that layer.
+    let len = event.exception.len();
+    for (idx, link) in chain.enumerate().skip(1) {
+        if let Some(nested) = link.downcast_ref::<NestedReport>() {
+            if let Some(stacktrace) = backtrace::stacktrace_from_report(nested) {
+                event.exception[len - 1 - idx].stacktrace = Some(stacktrace);
+            }
+        }
+    }
+}
+
+/// Applies the given [`EventOptions`] (that don't depend on the report's handler) and the
+/// current thread's context to the event.
+pub(crate) fn apply_options(event: &mut Event<'static>, options: &EventOptions) {
+    // Backtraces are usually not attached in the first place, but some capture paths do so
+    // before the options are applied.
+    if options.skip_backtraces {
+        for exc in event.exception.iter_mut() {
+            exc.stacktrace = None;
+        }
+
+        event.extra.remove("raw_backtrace");
+    }
+
+    if let Some(level) = options.level {
+        event.level = level;
+    }
+
+    if let Some(in_app) = &options.in_app_fn {
+        for frame in event
+            .exception
+            .iter_mut()
+            .filter_map(|exc| exc.stacktrace.as_mut())
+            .flat_map(|stacktrace| stacktrace.frames.iter_mut())
+        {
+            frame.in_app = Some(in_app(frame));
+        }
+    }
+
+    if let Some(depth) = options.in_app_chain_depth {
+        // Exceptions are ordered from innermost to outermost, so the outermost `depth`
+        // exceptions are the last ones.
+        let inner = event.exception.len().saturating_sub(depth);
+        for frame in event.exception[..inner]
+            .iter_mut()
+            .filter_map(|exc| exc.stacktrace.as_mut())
+            .flat_map(|stacktrace| stacktrace.frames.iter_mut())
+        {
+            frame.in_app = Some(false);
+        }
+    }
+
+    if options.source_context {
+        source_context::attach_source_context(event);
+    }
+
+    context::merge_into(&mut event.tags);
+
+    for (tag, var) in &options.env_tags {
+        if let Ok(value) = std::env::var(var) {
+            event.tags.insert(tag.clone(), value);
+        }
+    }
+
+    // Every link of the chain is represented as its own exception.
+    if options.tag_chain_depth {
+        let depth = event.exception.len().to_string();
+        event.tags.insert("error.chain_depth".into(), depth);
+    }
+
+    let mut truncated = 0;
+    if let Some(depth) = options.max_chain_depth {
+        // Exceptions are ordered from innermost to outermost, so the innermost ones are dropped.
+        truncated = event.exception.len().saturating_sub(depth);
+        event.exception.values.drain(..truncated);
+    }
+
+    if let Some(max) = options.max_frames {
+        for frames in event
+            .exception
+            .iter_mut()
+            .filter_map(|exc| exc.stacktrace.as_mut())
+            .map(|stacktrace| &mut stacktrace.frames)
+        {
+            // Frames are ordered from the oldest to the most recent call.
+            frames.drain(..frames.len().saturating_sub(max));
+        }
+    }
+
+    if options.tag_panic_strategy {
+        let strategy = if cfg!(panic = "abort") {
+            "abort"
+        } else {
+            "unwind"
+        };
+        event
+            .tags
+            .insert("rust.panic_strategy".into(), strategy.into());
+    }
+
+    // This needs to happen after anything that depends on the individual links.
+    if options.single_exception {
+        collapse_exceptions(event);
+    } else if truncated > 0 {
+        event
+            .exception
+            .values
+            .insert(0, truncation_marker(truncated));
+    }
+
+    #[cfg(feature = "tokio")]
+    if let Some(id) = tokio::task::try_id() {
+        event.tags.insert("tokio.task_id".into(), id.to_string());
+    }
+
+    if options.strip_ansi {
+        for value in event
+            .exception
+            .iter_mut()
+            .filter_map(|exc| exc.value.as_mut())
+        {
+            *value = options::sanitize(value);
+        }
+
+        if let Some(message) = &mut event.message {
+            *message = options::sanitize(message);
+        }
+    }
+
+    if let Some(transform) = &options.type_name_transform {
+        for exc in event.exception.iter_mut() {
+            exc.ty = transform(&exc.ty);
+        }
+    }
+
+    if let Some(platform) = &options.platform {
+        event.platform = platform.clone();
+    }
+
+    if let Some(dist) = &options.dist {
+        event.dist = Some(dist.clone());
+    }
+
+    if let Some(server_name) = &options.server_name {
+        event.server_name = Some(server_name.clone());
+    }
+
+    if let Some(module) = &options.default_module {
+        for exc in event
+            .exception
+            .iter_mut()
+            .filter(|exc| exc.module.is_none())
+        {
+            exc.module = Some(module.to_string());
+        }
+    }
+
+    #[cfg(feature = "modules")]
+    if options.include_modules {
+        event.modules.extend(modules::modules());
+    }
+
+    #[cfg(feature = "process-stats")]
+    if options.include_process_stats {
+        if let Some(context) = process::process_context() {
+            event.contexts.insert("process".into(), context);
+        }
+    }
+
+    if let Some(max) = options.max_value_length {
+        let values = event
+            .exception
+            .iter_mut()
+            .filter_map(|exc| exc.value.as_mut())
+            .chain(event.message.as_mut());
+
+        for value in values {
+            options::truncate(value, max);
+        }
+    }
+
+    options.redact(event);
+    options.trim_payload(event);
+}
+
+/// Creates the synthetic exception that stands for the innermost `truncated` links of the
+/// chain, which were dropped because of [`EventOptions::max_chain_depth`].
+fn truncation_marker(truncated: usize) -> Exception {
+    Exception {
+        ty: "TruncatedChain".into(),
+        value: Some(format!("{truncated} more sources truncated")),
+        mechanism: Some(Mechanism {
+            ty: "eyre".into(),
+            handled: Some(true),
+            synthetic: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Collapses all exceptions of the event into the innermost one, with the whole chain as
+/// its value (like the report's alternate `Display`) and the outermost stacktrace.
+fn collapse_exceptions(event: &mut Event<'static>) {
+    if event.exception.len() < 2 {
+        return;
+    }
+
+    let value = event
+        .exception
+        .iter()
+        .rev()
+        .filter_map(|exc| exc.value.as_deref())
+        .collect::<Vec<_>>()
+        .join(": ");
+
+    let stacktrace = event
+        .exception
+        .iter_mut()
+        .rev()
+        .find_map(|exc| exc.stacktrace.take());
+
+    event.exception.values.truncate(1);
+    let exc = &mut event.exception.values[0];
+    exc.value = Some(value);
+    exc.stacktrace = stacktrace;
+
+    // The exception stands for the whole chain now, rather than its innermost link.
+    if let Some(mechanism) = &mut exc.mechanism {
+        mechanism.data.remove("chain_index");
+    }
 }
 
 /// Extension trait to implement a `capture_report` method on any implementations.
@@ -59,16 +1133,154 @@ pub trait CaptureReportExt: private::Sealed {
     /// Captures a [`Report`] and sends it to Sentry. Refer to the top-level
     /// module documentation on how to use this method.
     fn capture_report(&self, report: &Report) -> Uuid;
+
+    /// Captures a [`Report`] within a temporary scope that is configured by `f`, i.e. to
+    /// tag it with the request's id. Refer to [`capture_report_with_scope`] for an example.
+    fn capture_report_with_scope<F: FnOnce(&mut Scope)>(&self, report: &Report, f: F) -> Uuid;
+
+    /// Captures a [`Report`] with the given [`Level`]. Refer to [`capture_report_with_level`]
+    /// for an example.
+    fn capture_report_with_level(&self, report: &Report, level: Level) -> Uuid;
+
+    /// Captures a [`Report`] with the given tags and extra data. Refer to
+    /// [`capture_report_with`] for an example.
+    fn capture_report_with<'k, T, TV, E, EV>(&self, report: &Report, tags: T, extra: E) -> Uuid
+    where
+        T: IntoIterator<Item = (&'k str, TV)>,
+        TV: Into<Value>,
+        E: IntoIterator<Item = (&'k str, EV)>,
+        EV: Into<Value>;
 }
 
 impl CaptureReportExt for Hub {
     fn capture_report(&self, report: &Report) -> Uuid {
-        self.capture_event(event_from_report(report))
+        if let Some(event_id) = sink::spool_report(self, report) {
+            return event_id;
+        }
+
+        if !should_capture(self, report) {
+            return Uuid::nil();
+        }
+
+        hooks::capture_event(self, report, event_from_report(report))
+    }
+
+    fn capture_report_with_scope<F: FnOnce(&mut Scope)>(&self, report: &Report, f: F) -> Uuid {
+        if !should_capture(self, report) {
+            return Uuid::nil();
+        }
+
+        let _guard = self.push_scope();
+        self.configure_scope(f);
+        hooks::capture_event(self, report, event_from_report(report))
     }
+
+    fn capture_report_with_level(&self, report: &Report, level: Level) -> Uuid {
+        if !should_capture(self, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        event.level = level;
+
+        hooks::capture_event(self, report, event)
+    }
+
+    fn capture_report_with<'k, T, TV, E, EV>(&self, report: &Report, tags: T, extra: E) -> Uuid
+    where
+        T: IntoIterator<Item = (&'k str, TV)>,
+        TV: Into<Value>,
+        E: IntoIterator<Item = (&'k str, EV)>,
+        EV: Into<Value>,
+    {
+        if !should_capture(self, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        event.tags.extend(tags.into_iter().map(|(key, value)| {
+            let value = match value.into() {
+                Value::String(value) => value,
+                value => value.to_string(),
+            };
+
+            (key.to_owned(), value)
+        }));
+
+        event.extra.extend(
+            extra
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.into())),
+        );
+
+        hooks::capture_event(self, report, event)
+    }
+}
+
+/// Extension trait to capture anything that can be converted into a [`Report`] (like custom
+/// error types) directly, without converting it by hand first.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::CaptureIntoExt;
+/// use std::{error::Error, fmt};
+///
+/// #[derive(Debug)]
+/// struct QuotaExceeded;
+///
+/// impl fmt::Display for QuotaExceeded {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("quota exceeded")
+///     }
+/// }
+///
+/// impl Error for QuotaExceeded {}
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     QuotaExceeded.capture_into();
+/// });
+///
+/// assert_eq!(events[0].exception[0].value.as_deref(), Some("quota exceeded"));
+/// ```
+pub trait CaptureIntoExt {
+    /// Converts `self` into a [`Report`] and captures it with [`capture_report`].
+    fn capture_into(self) -> Uuid;
+}
+
+impl<T: Into<Report>> CaptureIntoExt for T {
+    fn capture_into(self) -> Uuid {
+        capture_report(&self.into())
+    }
+}
+
+/// Checks if the given [`Report`] should be captured at all. This is done before the
+/// event is created, since creating it (i.e. parsing the backtrace) can be expensive
+/// and is wasted if the [`Hub`] has no enabled client to send the event with.
+///
+/// Since the sample rates of [`ReportMapper`]s are applied here, this must only be checked
+/// once per capture.
+pub(crate) fn should_capture(hub: &Hub, report: &Report) -> bool {
+    should_capture_error(hub, report.as_ref())
+}
+
+/// Same as [`should_capture`], but for any [`Error`].
+pub(crate) fn should_capture_error(hub: &Hub, err: &(dyn Error + 'static)) -> bool {
+    hub.client().is_some_and(|client| client.is_enabled())
+        && is_capture_allowed(err)
+        && !mapper::is_sampled_out(hub, err)
+}
+
+/// Checks if the given error isn't dropped by the kill switch or a suppression, regardless
+/// of whether a client is bound.
+pub(crate) fn is_capture_allowed(err: &(dyn Error + 'static)) -> bool {
+    !kill_switch::is_kill_switch_active()
+        && !suppress::is_suppressed(err)
+        && !mapper::is_suppressed(err)
 }
 
 mod private {
     pub trait Sealed {}
 
     impl Sealed for sentry_core::Hub {}
+    impl<T> Sealed for Result<T, eyre::Report> {}
 }
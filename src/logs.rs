@@ -0,0 +1,156 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture, EyreIntegration};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Event, Level, Log, LogLevel, Map},
+    types::Uuid,
+    Hub,
+};
+use std::{error::Error, time::SystemTime};
+
+/// Captures a [`Report`] as an event (for alerting) and emits a structured log with the
+/// report's message (for log search) from a single call. The log carries the event's id as
+/// the `sentry.event_id` attribute and the chain of the report as the `eyre.chain.{index}`
+/// attributes, and both are linked to the same trace.
+///
+/// This sends the report twice, so it's meant for high-severity errors only. Logs need to
+/// be enabled with [`ClientOptions::enable_logs`](sentry_core::ClientOptions::enable_logs);
+/// otherwise only the event is sent.
+///
+/// ## Example
+/// ```
+/// use sentry::{
+///     protocol::{EnvelopeItem, ItemContainer, LogLevel},
+///     ClientOptions,
+/// };
+/// use sentry_eyre::capture_report_and_log;
+///
+/// let envelopes = sentry::test::with_captured_envelopes_options(
+///     || {
+///         capture_report_and_log(&eyre::eyre!("payment failed"), LogLevel::Error);
+///     },
+///     ClientOptions {
+///         enable_logs: true,
+///         ..Default::default()
+///     },
+/// );
+///
+/// let items = envelopes.iter().flat_map(|envelope| envelope.items()).collect::<Vec<_>>();
+/// let Some(EnvelopeItem::Event(event)) = items
+///     .iter()
+///     .find(|item| matches!(item, EnvelopeItem::Event(_)))
+/// else {
+///     panic!("expected an event");
+/// };
+///
+/// let Some(EnvelopeItem::ItemContainer(ItemContainer::Logs(logs))) = items
+///     .iter()
+///     .find(|item| matches!(item, EnvelopeItem::ItemContainer(_)))
+/// else {
+///     panic!("expected logs");
+/// };
+///
+/// assert_eq!(logs[0].body, "payment failed");
+/// assert_eq!(
+///     logs[0].attributes["sentry.event_id"].0,
+///     event.event_id.simple().to_string()
+/// );
+/// ```
+pub fn capture_report_and_log(report: &Report, level: LogLevel) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        event.level = match level {
+            LogLevel::Trace | LogLevel::Debug => Level::Debug,
+            LogLevel::Info => Level::Info,
+            LogLevel::Warn => Level::Warning,
+            LogLevel::Error => Level::Error,
+            LogLevel::Fatal => Level::Fatal,
+        };
+
+        let log = Log {
+            level,
+            ..log_from_error(report.as_ref(), &event)
+        };
+
+        let event_id = hooks::capture_event(hub, report, event);
+
+        // The bound integration already emitted a log alongside the event.
+        if !emits_logs(hub) {
+            capture_log(hub, log, event_id);
+        }
+
+        event_id
+    })
+}
+
+/// Checks if the [`EyreIntegration`] that is bound to the given [`Hub`] emits a structured log
+/// alongside every event, see [`EyreIntegration::emit_logs`].
+pub(crate) fn emits_logs(hub: &Hub) -> bool {
+    hub.with_integration(|integration: &EyreIntegration| integration.emit_logs)
+}
+
+/// Creates the structured log that is emitted alongside the event of the given (outermost)
+/// error, with the error's message as its body and the level of the event.
+pub(crate) fn log_from_error(err: &(dyn Error + 'static), event: &Event<'_>) -> Log {
+    let mut attributes = Map::new();
+    if let Some(exc) = event.exception.last() {
+        attributes.insert("error.type".into(), exc.ty.clone().into());
+    }
+
+    let chain = std::iter::successors(Some(err), |&err| err.source());
+    for (index, link) in chain.enumerate() {
+        attributes.insert(format!("eyre.chain.{index}"), link.to_string().into());
+    }
+
+    Log {
+        level: match event.level {
+            Level::Debug => LogLevel::Debug,
+            Level::Info => LogLevel::Info,
+            Level::Warning => LogLevel::Warn,
+            Level::Error => LogLevel::Error,
+            Level::Fatal => LogLevel::Fatal,
+        },
+        body: err.to_string(),
+        trace_id: None,
+        timestamp: SystemTime::now(),
+        severity_number: None,
+        attributes,
+    }
+}
+
+/// Captures the given log with the [`Hub`], linking it to the event with the given id unless
+/// no event was captured.
+pub(crate) fn capture_log(hub: &Hub, mut log: Log, event_id: Uuid) {
+    if !event_id.is_nil() {
+        log.attributes.insert(
+            "sentry.event_id".into(),
+            event_id.simple().to_string().into(),
+        );
+    }
+
+    hub.capture_log(log);
+}
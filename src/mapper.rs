@@ -0,0 +1,300 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{fingerprint::hash_error, sampling::is_hash_sampled, Fingerprint};
+use sentry_core::{
+    protocol::{Event, Level},
+    Hub,
+};
+use std::{
+    collections::hash_map::RandomState,
+    error::Error,
+    hash::BuildHasher,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+type Matcher = Arc<dyn Fn(&(dyn Error + 'static)) -> bool + Send + Sync>;
+
+/// How matching reports are sampled.
+#[derive(Debug, Clone, Copy)]
+enum SampleRate {
+    /// A random decision is made for every capture.
+    Random(f32),
+
+    /// The same decision is made for every capture of the same report.
+    Consistent(f32),
+}
+
+static MAPPERS: RwLock<Vec<Arc<ReportMapper>>> = RwLock::new(Vec::new());
+
+/// Overrides how reports that contain a matching error anywhere in their chain are captured,
+/// without handling them at every call site. Mappers are registered globally with
+/// [`register`](ReportMapper::register) and applied in the order they were registered, so
+/// later mappers win if several match the same report.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+/// use sentry_eyre::{capture_report, ReportMapper};
+/// use std::io;
+///
+/// ReportMapper::for_type_where(|err: &io::Error| err.kind() == io::ErrorKind::TimedOut)
+///     .level(Level::Warning)
+///     .fingerprint(["db-pool"])
+///     .tag("subsystem", "db")
+///     .register();
+///
+/// ReportMapper::for_type_where(|err: &io::Error| err.kind() == io::ErrorKind::BrokenPipe)
+///     .suppress()
+///     .register();
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::Report::new(io::Error::from(io::ErrorKind::TimedOut))
+///         .wrap_err("failed to acquire a connection");
+///
+///     capture_report(&report);
+///     capture_report(&eyre::Report::new(io::Error::from(io::ErrorKind::BrokenPipe)));
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].level, Level::Warning);
+/// assert_eq!(events[0].fingerprint[..], ["db-pool"]);
+/// assert_eq!(events[0].tags["subsystem"], "db");
+/// ```
+pub struct ReportMapper {
+    matcher: Matcher,
+    level: Option<Level>,
+    fingerprint: Option<Fingerprint>,
+    tags: Vec<(String, String)>,
+    suppress: bool,
+    expected: bool,
+    sample_rate: Option<SampleRate>,
+}
+
+impl ReportMapper {
+    /// Creates a mapper for reports that contain an error of type `T`.
+    pub fn for_type<T: Error + 'static>() -> Self {
+        Self::when(|err| err.is::<T>())
+    }
+
+    /// Creates a mapper for reports that contain an error of type `T` which matches the
+    /// given predicate, i.e. a specific variant of an error enum.
+    pub fn for_type_where<T, F>(predicate: F) -> Self
+    where
+        T: Error + 'static,
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        Self::when(move |err| err.downcast_ref::<T>().is_some_and(&predicate))
+    }
+
+    /// Creates a mapper for reports that contain an error which matches the given predicate.
+    pub fn when<F>(predicate: F) -> Self
+    where
+        F: Fn(&(dyn Error + 'static)) -> bool + Send + Sync + 'static,
+    {
+        ReportMapper {
+            matcher: Arc::new(predicate),
+            level: None,
+            fingerprint: None,
+            tags: Vec::new(),
+            suppress: false,
+            expected: false,
+            sample_rate: None,
+        }
+    }
+
+    /// Overrides the [`Level`] of the event.
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// Overrides the fingerprint of the event.
+    pub fn fingerprint<F: Into<Fingerprint>>(mut self, fingerprint: F) -> Self {
+        self.fingerprint = Some(fingerprint.into());
+        self
+    }
+
+    /// Adds a tag to the event.
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Suppresses matching reports entirely, like [`add_suppressed_type`](crate::add_suppressed_type)
+    /// does for a whole type.
+    pub fn suppress(mut self) -> Self {
+        self.suppress = true;
+        self
+    }
+
+    /// Marks matching reports as expected (i.e. user errors), which downgrades their events
+    /// to [`Level::Warning`] if [`EventOptions::infer_level`](crate::EventOptions::infer_level)
+    /// is enabled.
+    pub fn expected(mut self) -> Self {
+        self.expected = true;
+        self
+    }
+
+    /// Only captures the given fraction (clamped to `0.0..=1.0`) of matching reports, with a
+    /// random decision for every capture.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{capture_report, ReportMapper};
+    /// use std::num::ParseIntError;
+    ///
+    /// ReportMapper::for_type::<ParseIntError>().sample_rate(0.1).register();
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     for _ in 0..1000 {
+    ///         capture_report(&eyre::Report::new("x".parse::<u8>().unwrap_err()));
+    ///     }
+    ///
+    ///     capture_report(&eyre::eyre!("a rare error"));
+    /// });
+    ///
+    /// assert!((50..150).contains(&(events.len() - 1)));
+    /// assert_eq!(events.last().unwrap().exception[0].value.as_deref(), Some("a rare error"));
+    /// ```
+    pub fn sample_rate(mut self, rate: f32) -> Self {
+        self.sample_rate = Some(SampleRate::Random(rate.clamp(0.0, 1.0)));
+        self
+    }
+
+    /// Same as [`sample_rate`](Self::sample_rate), but makes the same decision for every
+    /// capture of the same report (like [`ConsistentSampler`](crate::ConsistentSampler)),
+    /// so sampling is stable across replicas and restarts.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{capture_report, ReportMapper};
+    /// use std::io;
+    ///
+    /// ReportMapper::for_type::<io::Error>()
+    ///     .consistent_sample_rate(0.25)
+    ///     .register();
+    ///
+    /// let report = |path: usize| {
+    ///     eyre::Report::new(io::Error::other(format!("{path} not found")))
+    /// };
+    ///
+    /// let first = sentry::test::with_captured_events(|| {
+    ///     for path in 0..1000 {
+    ///         capture_report(&report(path));
+    ///     }
+    /// });
+    ///
+    /// let second = sentry::test::with_captured_events(|| {
+    ///     for path in 0..1000 {
+    ///         capture_report(&report(path));
+    ///     }
+    /// });
+    ///
+    /// assert!((150..350).contains(&first.len()));
+    /// assert_eq!(first.len(), second.len());
+    /// ```
+    pub fn consistent_sample_rate(mut self, rate: f32) -> Self {
+        self.sample_rate = Some(SampleRate::Consistent(rate.clamp(0.0, 1.0)));
+        self
+    }
+
+    /// Registers the mapper globally, so it's consulted whenever an event is created from a
+    /// report.
+    pub fn register(self) {
+        MAPPERS.write().unwrap().push(Arc::new(self));
+    }
+
+    fn matches(&self, err: &(dyn Error + 'static)) -> bool {
+        std::iter::successors(Some(err), |&err| err.source()).any(|err| (self.matcher)(err))
+    }
+}
+
+/// Removes all mappers that were registered with [`ReportMapper::register`].
+pub fn clear_report_mappers() {
+    MAPPERS.write().unwrap().clear();
+}
+
+/// Returns the registered mappers that match the error's chain, in the order they were
+/// registered.
+fn matching(err: &(dyn Error + 'static)) -> Vec<Arc<ReportMapper>> {
+    // Mappers are cloned out of the registry so that predicates can't deadlock it.
+    let mappers = MAPPERS.read().unwrap().clone();
+    mappers
+        .into_iter()
+        .filter(|mapper| mapper.matches(err))
+        .collect()
+}
+
+/// Checks if a registered mapper suppresses the error.
+pub(crate) fn is_suppressed(err: &(dyn Error + 'static)) -> bool {
+    matching(err).iter().any(|mapper| mapper.suppress)
+}
+
+/// Checks if a registered mapper marks the error as expected.
+pub(crate) fn is_expected(err: &(dyn Error + 'static)) -> bool {
+    matching(err).iter().any(|mapper| mapper.expected)
+}
+
+/// Checks if the error is dropped by the sample rate of the last registered mapper that
+/// matches it and has one.
+pub(crate) fn is_sampled_out(hub: &Hub, err: &(dyn Error + 'static)) -> bool {
+    let Some(rate) = matching(err)
+        .iter()
+        .rev()
+        .find_map(|mapper| mapper.sample_rate)
+    else {
+        return false;
+    };
+
+    match rate {
+        // Without a client (i.e. when the report is stored with a sink), the decision is made
+        // here instead.
+        SampleRate::Random(rate) => !match hub.client() {
+            Some(client) => client.sample_should_send(rate),
+            None => rate >= 1.0 || random() < rate,
+        },
+
+        SampleRate::Consistent(rate) => !is_hash_sampled(hash_error(err), rate),
+    }
+}
+
+/// Returns a random number in `0.0..1.0`, without pulling in a random number generator.
+fn random() -> f32 {
+    let bits = RandomState::new().hash_one(Instant::now());
+    (bits >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Applies the overrides of all registered mappers that match the error to the event.
+pub(crate) fn apply(err: &(dyn Error + 'static), event: &mut Event<'static>) {
+    for mapper in matching(err) {
+        if let Some(level) = mapper.level {
+            event.level = level;
+        }
+
+        if let Some(fingerprint) = &mapper.fingerprint {
+            event.fingerprint = fingerprint.clone().into();
+        }
+
+        event.tags.extend(mapper.tags.iter().cloned());
+    }
+}
@@ -0,0 +1,109 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use eyre::Report;
+use std::{error::Error, fmt, ops::Deref};
+
+/// Wrapper that makes a [`Report`] usable as the source of another error.
+///
+/// Wrapping a report with [`WrapErr`](eyre::WrapErr) keeps a single backtrace for the whole
+/// chain, but a [`Report`] that is stored inside of another error type (i.e. as the source of
+/// a custom error) is invisible to [`event_from_report`](crate::event_from_report) since it
+/// doesn't implement [`Error`]. Storing it as a [`NestedReport`] instead lets its backtrace be
+/// attached to the corresponding exception of the event.
+///
+/// The wrapper is transparent: it displays as the wrapped report, and its source is the
+/// wrapped report's source.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{event_from_report, NestedReport};
+/// use std::{error::Error, fmt};
+///
+/// #[derive(Debug)]
+/// struct JobError(NestedReport);
+///
+/// impl fmt::Display for JobError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("job failed")
+///     }
+/// }
+///
+/// impl Error for JobError {
+///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// # #[cfg(feature = "stable-backtrace")]
+/// # {
+/// #     std::env::set_var("RUST_BACKTRACE", "1");
+/// #     stable_eyre::install().unwrap();
+/// # }
+/// let inner = eyre::eyre!("connection reset");
+/// let report = eyre::Report::new(JobError(inner.into()));
+/// let event = event_from_report(&report);
+///
+/// assert_eq!(event.exception[0].value.as_deref(), Some("connection reset"));
+/// assert_eq!(event.exception[1].value.as_deref(), Some("job failed"));
+/// # #[cfg(feature = "stable-backtrace")]
+/// # assert!(event.exception[0].stacktrace.is_some());
+/// ```
+pub struct NestedReport(Report);
+
+impl NestedReport {
+    /// Returns the wrapped [`Report`].
+    pub fn into_inner(self) -> Report {
+        self.0
+    }
+}
+
+impl From<Report> for NestedReport {
+    fn from(report: Report) -> Self {
+        NestedReport(report)
+    }
+}
+
+impl Deref for NestedReport {
+    type Target = Report;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Debug for NestedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for NestedReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl Error for NestedReport {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
@@ -0,0 +1,1018 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::EyreIntegration;
+use eyre::EyreHandler;
+use sentry_core::{
+    protocol::{Context, Event, Frame, Level, Value},
+    Hub,
+};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The amount of characters that exception values and the message are truncated to when
+/// trimming an event to [`EventOptions::max_payload_bytes`].
+const MAX_TRIMMED_VALUE_LEN: usize = 1024;
+
+static DEFAULT_OPTIONS: RwLock<Option<Arc<EventOptions>>> = RwLock::new(None);
+
+thread_local! {
+    static SCOPED_OPTIONS: RefCell<Vec<Arc<EventOptions>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Options to customize how a [`Report`](eyre::Report) is represented as a Sentry event. Refer
+/// to [`event_from_report_with_options`](crate::event_from_report_with_options) on how these
+/// are applied.
+#[derive(Clone, Default)]
+pub struct EventOptions {
+    /// Sets the `module` of every exception that doesn't have one, which Sentry uses for
+    /// grouping and display. Use [`default_module!`](crate::default_module) to namespace the
+    /// exceptions by the name of your crate.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     default_module: Some(sentry_eyre::default_module!()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// assert_eq!(event.exception[0].module.as_deref(), Some(env!("CARGO_PKG_NAME")));
+    /// ```
+    pub default_module: Option<Cow<'static, str>>,
+
+    /// Sets the event's `dist`, which distinguishes between builds (or distributions) of
+    /// the same release, i.e. per-platform builds of a desktop app.
+    pub dist: Option<Cow<'static, str>>,
+
+    /// Tags that are read from environment variables at capture time, as `(tag, variable)`
+    /// pairs (i.e. `("pod", "POD_NAME")`). Variables that aren't set are skipped.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// std::env::set_var("REGION", "eu-west-1");
+    ///
+    /// let options = EventOptions {
+    ///     env_tags: vec![
+    ///         ("region".into(), "REGION".into()),
+    ///         ("pod".into(), "SENTRY_EYRE_UNSET_POD_NAME".into()),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// assert_eq!(event.tags["region"], "eu-west-1");
+    /// assert!(!event.tags.contains_key("pod"));
+    /// ```
+    pub env_tags: Vec<(String, String)>,
+
+    /// Extracts arbitrary data out of the report's [`EyreHandler`], which will be inserted
+    /// into the event's `extra`. This is useful for custom handlers that store structured
+    /// context besides what `stable-eyre` or `color-eyre` provide.
+    #[allow(clippy::type_complexity)]
+    pub extract_handler_context:
+        Option<Arc<dyn Fn(&dyn EyreHandler) -> HashMap<String, Value> + Send + Sync>>,
+
+    /// Extracts the [`SpanTrace`](tracing_error::SpanTrace) that a custom [`EyreHandler`]
+    /// captured, which is attached to the event with [`attach_span_trace`](crate::attach_span_trace).
+    /// Without it, the `SpanTrace` is looked up in the report's chain (i.e. from a
+    /// [`TracedError`](tracing_error::TracedError)) and in `color-eyre`'s handler.
+    ///
+    /// **Feature:** `tracing` (*disabled by default*)
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::EyreHandler;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    /// use std::{error::Error, fmt, sync::Arc};
+    /// use tracing_error::{ErrorLayer, SpanTrace};
+    /// use tracing_subscriber::prelude::*;
+    ///
+    /// struct TracingHandler {
+    ///     span_trace: SpanTrace,
+    /// }
+    ///
+    /// impl EyreHandler for TracingHandler {
+    ///     fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         write!(f, "{error}\n\n{}", self.span_trace)
+    ///     }
+    /// }
+    ///
+    /// tracing_subscriber::registry().with(ErrorLayer::default()).init();
+    /// eyre::set_hook(Box::new(|_| {
+    ///     Box::new(TracingHandler {
+    ///         span_trace: SpanTrace::capture(),
+    ///     })
+    /// }))
+    /// .unwrap();
+    ///
+    /// let options = EventOptions {
+    ///     extract_span_trace: Some(Arc::new(|handler| {
+    ///         let handler = handler.downcast_ref::<TracingHandler>()?;
+    ///         Some(handler.span_trace.clone())
+    ///     })),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = tracing::info_span!("import", file = "users.csv")
+    ///     .in_scope(|| eyre::eyre!("row 12 is invalid"));
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.breadcrumbs[0].message.as_deref(), Some("rust_out::import"));
+    /// assert!(event.contexts.contains_key("trace_spans"));
+    /// ```
+    #[cfg(feature = "tracing")]
+    #[allow(clippy::type_complexity)]
+    pub extract_span_trace:
+        Option<Arc<dyn Fn(&dyn EyreHandler) -> Option<tracing_error::SpanTrace> + Send + Sync>>,
+
+    /// Whether to attach the report's alternate `Display` (`{:#}`), which renders the whole
+    /// chain as `outer: ...: inner`, as the `error_chain` extra.
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     include_formatted_chain: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///     .wrap_err("query failed")
+    ///     .unwrap_err();
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.extra["error_chain"], "query failed: connection reset");
+    /// ```
+    pub include_formatted_chain: bool,
+
+    /// Whether to attach the versions of the crates in the dependency graph as `event.modules`.
+    /// They're gathered from the `Cargo.lock` of the workspace at build time, so the map is
+    /// empty if it couldn't be found.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     include_modules: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("unexpected response"), &options);
+    /// assert!(!event.modules.is_empty());
+    /// assert!(event.modules.contains_key("eyre"));
+    /// ```
+    #[cfg(feature = "modules")]
+    pub include_modules: bool,
+
+    /// Whether to attach the process' uptime and memory usage as the `process` context.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Context;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     include_process_stats: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("out of memory"), &options);
+    /// let Some(Context::Other(process)) = event.contexts.get("process") else {
+    ///     panic!("expected a process context");
+    /// };
+    ///
+    /// assert!(process.contains_key("uptime_secs"));
+    /// assert!(process.contains_key("memory_rss_bytes"));
+    /// ```
+    #[cfg(feature = "process-stats")]
+    pub include_process_stats: bool,
+
+    /// Limits in-app frames to the outermost `N` exceptions of the chain; frames of all inner
+    /// exceptions (i.e. the library causes of a wrapped error) are marked as not in-app, so
+    /// Sentry focuses on the user-facing context. `None` (the default) doesn't limit them.
+    ///
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "stable-backtrace")]
+    /// # {
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions, NestedReport};
+    /// use std::{error::Error, fmt};
+    ///
+    /// #[derive(Debug)]
+    /// struct JobError(NestedReport);
+    ///
+    /// impl fmt::Display for JobError {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         f.write_str("job failed")
+    ///     }
+    /// }
+    ///
+    /// impl Error for JobError {
+    ///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+    ///         Some(&self.0)
+    ///     }
+    /// }
+    ///
+    /// std::env::set_var("RUST_BACKTRACE", "1");
+    /// stable_eyre::install().unwrap();
+    ///
+    /// let inner = eyre::eyre!("connection reset").wrap_err("query failed");
+    /// let report = eyre::Report::new(JobError(inner.into()));
+    /// let options = EventOptions {
+    ///     in_app_chain_depth: Some(1),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// let frames = |idx: usize| &event.exception[idx].stacktrace.as_ref().unwrap().frames;
+    ///
+    /// assert_eq!(event.exception.len(), 3);
+    /// assert!(frames(1).iter().all(|frame| frame.in_app == Some(false)));
+    /// assert!(frames(2).iter().any(|frame| frame.in_app == Some(true)));
+    /// # }
+    /// ```
+    pub in_app_chain_depth: Option<usize>,
+
+    /// Decides whether or not a stacktrace frame is in-app, based on its module, filename,
+    /// function, etc. This is invoked for every frame and takes precedence over the client's
+    /// [`in_app_include`](sentry_core::ClientOptions::in_app_include) and
+    /// [`in_app_exclude`](sentry_core::ClientOptions::in_app_exclude) prefixes.
+    ///
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "stable-backtrace")]
+    /// # {
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    /// use std::sync::Arc;
+    ///
+    /// std::env::set_var("RUST_BACKTRACE", "1");
+    /// stable_eyre::install().unwrap();
+    ///
+    /// let options = EventOptions {
+    ///     in_app_fn: Some(Arc::new(|frame| {
+    ///         frame.function.as_deref().is_some_and(|func| func.starts_with("rust_out::"))
+    ///     })),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// let frames = &event.exception.last().unwrap().stacktrace.as_ref().unwrap().frames;
+    ///
+    /// assert!(frames.iter().all(|frame| frame.in_app.is_some()));
+    /// # }
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub in_app_fn: Option<Arc<dyn Fn(&Frame) -> bool + Send + Sync>>,
+
+    /// Downgrades the event to [`Level::Warning`] if its chain contains an error that a
+    /// [`ReportMapper`](crate::ReportMapper) marked as [expected](crate::ReportMapper::expected),
+    /// i.e. a user error that's worth tracking but not alerting on. This takes precedence over
+    /// [`level`](Self::level), but not over a mapper's own level.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Level;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions, ReportMapper};
+    /// use std::num::ParseIntError;
+    ///
+    /// ReportMapper::for_type::<ParseIntError>().expected().register();
+    ///
+    /// let options = EventOptions {
+    ///     infer_level: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = eyre::Report::new("x".parse::<u8>().unwrap_err()).wrap_err("invalid port");
+    /// assert_eq!(event_from_report_with_options(&report, &options).level, Level::Warning);
+    ///
+    /// let report = eyre::eyre!("database is down");
+    /// assert_eq!(event_from_report_with_options(&report, &options).level, Level::Error);
+    /// ```
+    pub infer_level: bool,
+
+    /// Sets the event's [`Level`], which is [`Level::Error`] by default. Capture functions that
+    /// take an explicit level (like [`capture_fatal`](crate::capture_fatal)) override this.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry::protocol::Level;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     level: Some(Level::Warning),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("cache miss"), &options);
+    /// assert_eq!(event.level, Level::Warning);
+    /// ```
+    pub level: Option<Level>,
+
+    /// Limits the exceptions of the event to the outermost `N` links of the chain, dropping the
+    /// innermost causes of deeply wrapped reports. `None` (the default) keeps all of them.
+    ///
+    /// If any links were dropped, a synthetic `TruncatedChain` exception that tells how many is
+    /// added in place of the innermost cause (unless [`single_exception`](Self::single_exception)
+    /// is set).
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     max_chain_depth: Some(2),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///     .wrap_err("query failed")
+    ///     .wrap_err("sync failed")
+    ///     .unwrap_err();
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.exception.len(), 3);
+    /// assert_eq!(event.exception[0].ty, "TruncatedChain");
+    /// assert_eq!(event.exception[0].value.as_deref(), Some("1 more sources truncated"));
+    /// assert_eq!(event.exception[1].value.as_deref(), Some("query failed"));
+    /// ```
+    pub max_chain_depth: Option<usize>,
+
+    /// Limits every stacktrace of the event to its `N` most recent frames, dropping the oldest
+    /// ones. `None` (the default) keeps all of them.
+    ///
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "std-backtrace")]
+    /// # {
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// std::env::set_var("RUST_BACKTRACE", "1");
+    ///
+    /// let options = EventOptions {
+    ///     max_frames: Some(4),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// let stacktrace = event.exception[0].stacktrace.as_ref().unwrap();
+    /// assert_eq!(stacktrace.frames.len(), 4);
+    /// # }
+    /// ```
+    pub max_frames: Option<usize>,
+
+    /// Caps the size of the serialized event in bytes, so that it isn't rejected by Sentry for
+    /// being too large. Oversized events are trimmed until they fit, in this order: the `extra`
+    /// is dropped, then the breadcrumbs, the exception values and the message are truncated,
+    /// and then the oldest half of every stacktrace's frames is dropped repeatedly. What was
+    /// trimmed is recorded as the `payload_trimmed` extra.
+    ///
+    /// Captured events are trimmed once more after the scope was applied onto them, so that
+    /// the cap includes the scope's tags, extra and breadcrumbs as well. Attachments aren't
+    /// part of the event, and the client's integrations and `before_send` run afterwards, so
+    /// neither counts towards the cap.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{CaptureReportBuilder, EventOptions};
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("{}", "x".repeat(64 * 1024));
+    ///     CaptureReportBuilder::new(&report)
+    ///         .options(EventOptions {
+    ///             max_payload_bytes: Some(8 * 1024),
+    ///             ..Default::default()
+    ///         })
+    ///         .extra("response", "y".repeat(64 * 1024))
+    ///         .capture();
+    /// });
+    ///
+    /// assert!(serde_json::to_vec(&events[0]).unwrap().len() <= 8 * 1024);
+    /// assert!(!events[0].extra.contains_key("response"));
+    /// assert_eq!(events[0].extra["payload_trimmed"], serde_json::json!(["extra", "values"]));
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     sentry::configure_scope(|scope| scope.set_extra("session", "z".repeat(64 * 1024).into()));
+    ///     sentry::add_breadcrumb(sentry::Breadcrumb {
+    ///         message: Some("w".repeat(64 * 1024)),
+    ///         ..Default::default()
+    ///     });
+    ///
+    ///     let options = EventOptions {
+    ///         max_payload_bytes: Some(8 * 1024),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     sentry_eyre::with_options(options, || {
+    ///         sentry_eyre::capture_report(&eyre::eyre!("oh no"));
+    ///     });
+    /// });
+    ///
+    /// assert!(serde_json::to_vec(&events[0]).unwrap().len() <= 8 * 1024);
+    /// assert!(!events[0].extra.contains_key("session"));
+    /// assert_eq!(events[0].extra["payload_trimmed"], serde_json::json!(["extra", "breadcrumbs"]));
+    /// ```
+    pub max_payload_bytes: Option<usize>,
+
+    /// Truncates the value of every exception and the message of the event to `N` characters,
+    /// marking truncated ones with an ellipsis. `None` (the default) keeps them as-is.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     max_value_length: Some(8),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = eyre::eyre!("invalid response: {}", "x".repeat(1024));
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.exception[0].value.as_deref(), Some("invalid …"));
+    /// ```
+    pub max_value_length: Option<usize>,
+
+    /// Overrides the event's `platform`, which is `native` by default. This is useful for
+    /// services that embed another language runtime.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     platform: Some("javascript".into()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// assert_eq!(event.platform, "javascript");
+    /// ```
+    pub platform: Option<Cow<'static, str>>,
+
+    /// Whether to measure how long it took to create the event from the report (i.e. parsing
+    /// its backtrace), which is stored in milliseconds as the `capture_build_ms` extra.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     record_capture_timing: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// assert!(event.extra["capture_build_ms"].is_f64());
+    /// ```
+    pub record_capture_timing: bool,
+
+    /// Keys whose values are replaced with `"[REDACTED]"` in the event's `extra` and
+    /// custom contexts, no matter how deeply they are nested within objects or arrays.
+    /// Keys are matched case-insensitively.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{CaptureReportBuilder, EventOptions};
+    /// use serde_json::json;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("login failed");
+    ///     CaptureReportBuilder::new(&report)
+    ///         .options(EventOptions {
+    ///             redact_keys: vec!["password".into(), "token".into()],
+    ///             ..Default::default()
+    ///         })
+    ///         .extra(
+    ///             "request",
+    ///             json!({
+    ///                 "user": "noel",
+    ///                 "Password": "hunter2",
+    ///                 "sessions": [{ "token": "abc", "id": 1 }],
+    ///             }),
+    ///         )
+    ///         .capture();
+    /// });
+    ///
+    /// assert_eq!(
+    ///     events[0].extra["request"],
+    ///     json!({
+    ///         "user": "noel",
+    ///         "Password": "[REDACTED]",
+    ///         "sessions": [{ "token": "[REDACTED]", "id": 1 }],
+    ///     })
+    /// );
+    /// ```
+    pub redact_keys: Vec<String>,
+
+    /// Sets the event's `server_name`, i.e. when the host is detected differently than the
+    /// client does. When unset, the client fills it in.
+    pub server_name: Option<Cow<'static, str>>,
+
+    /// Whether to collapse the chain into a single exception, rather than one exception per
+    /// link. The exception has the type of the innermost error, the whole chain as its value
+    /// (like the report's alternate `Display`), and the report's backtrace.
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     single_exception: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///     .wrap_err("query failed")
+    ///     .unwrap_err();
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.exception.len(), 1);
+    /// assert_eq!(
+    ///     event.exception[0].value.as_deref(),
+    ///     Some("query failed: connection reset")
+    /// );
+    /// ```
+    pub single_exception: bool,
+
+    /// Whether to set the type of exceptions that only carry a message (i.e. `eyre!(status)` or
+    /// `eyre!(path.display())`) to `Error`. Their type name is otherwise parsed out of their
+    /// `Debug` output, which results in a synthetic type like `404`.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     simplify_string_errors: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let status = 404;
+    /// let report = eyre::eyre!(status);
+    /// let plain = event_from_report_with_options(&report, &EventOptions::default());
+    /// let simplified = event_from_report_with_options(&report, &options);
+    ///
+    /// assert_eq!(plain.exception[0].ty, "404");
+    /// assert_eq!(simplified.exception[0].ty, "Error");
+    /// assert_eq!(simplified.exception[0].value.as_deref(), Some("404"));
+    /// ```
+    pub simplify_string_errors: bool,
+
+    /// Whether to leave out the backtraces of the report (and of nested reports), i.e. if
+    /// they're too noisy or too expensive to capture for a service.
+    pub skip_backtraces: bool,
+
+    /// Whether to attach the source code around the line of the most recent in-app frames
+    /// (up to 10 of them) as their `pre_context`, `context_line` and `post_context`. Frames
+    /// whose source file can't be read at runtime are skipped.
+    ///
+    /// ## Example
+    /// ```
+    /// # #[cfg(feature = "stable-backtrace")]
+    /// # {
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    /// use std::sync::Arc;
+    ///
+    /// std::env::set_var("RUST_BACKTRACE", "1");
+    /// stable_eyre::install().unwrap();
+    ///
+    /// // Doctests are built without debug info, so `eyre`'s frame of where the report was
+    /// // created is used instead.
+    /// let options = EventOptions {
+    ///     in_app_fn: Some(Arc::new(|frame| {
+    ///         frame.function.as_deref() == Some("eyre::private::format_err")
+    ///     })),
+    ///     source_context: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("oh no"), &options);
+    /// let frames = &event.exception[0].stacktrace.as_ref().unwrap().frames;
+    /// let frame = frames.iter().find(|frame| frame.in_app == Some(true)).unwrap();
+    ///
+    /// assert!(frame.context_line.is_some());
+    /// assert_eq!(frame.pre_context.len(), 5);
+    /// assert!(frames.iter().all(|frame| frame.in_app == Some(true) || frame.context_line.is_none()));
+    /// # }
+    /// ```
+    pub source_context: bool,
+
+    /// Whether to strip ANSI escape sequences (i.e. colors that leak into an error's `Display`)
+    /// off the exception values and the message, and to replace any other control characters
+    /// with `�`, since both render badly in Sentry.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     strip_ansi: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = eyre::eyre!("\x1b[31mconnection reset\x1b[0m by \x07peer");
+    /// let event = event_from_report_with_options(&report, &options);
+    ///
+    /// assert_eq!(event.exception[0].value.as_deref(), Some("connection reset by �peer"));
+    /// ```
+    pub strip_ansi: bool,
+
+    /// Whether to tag the event with the number of links in the report's chain as
+    /// `error.chain_depth`, to see how deeply errors are typically wrapped.
+    ///
+    /// ## Example
+    /// ```
+    /// use eyre::WrapErr;
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     tag_chain_depth: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let report = Err::<(), _>(eyre::eyre!("connection reset"))
+    ///     .wrap_err("query failed")
+    ///     .wrap_err("sync failed")
+    ///     .unwrap_err();
+    ///
+    /// let event = event_from_report_with_options(&report, &options);
+    /// assert_eq!(event.tags["error.chain_depth"], "3");
+    /// ```
+    pub tag_chain_depth: bool,
+
+    /// Whether to tag the event with the panic strategy the crate was compiled with as
+    /// `rust.panic_strategy` (either `unwind` or `abort`), which affects how crashes are
+    /// triaged for binaries that are shipped in multiple configurations.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::{event_from_report_with_options, EventOptions};
+    ///
+    /// let options = EventOptions {
+    ///     tag_panic_strategy: true,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let event = event_from_report_with_options(&eyre::eyre!("worker crashed"), &options);
+    /// assert!(["unwind", "abort"].contains(&event.tags["rust.panic_strategy"].as_str()));
+    /// ```
+    pub tag_panic_strategy: bool,
+
+    /// Transforms the type name of every exception, i.e. to make fully-qualified Rust type
+    /// names more readable in the Sentry UI. See [`strip_module_path`] for a transform that
+    /// can be used.
+    #[allow(clippy::type_complexity)]
+    pub type_name_transform: Option<Arc<dyn Fn(&str) -> String + Send + Sync>>,
+}
+
+impl EventOptions {
+    /// Redacts the [`redact_keys`](EventOptions::redact_keys) in the event's `extra` and
+    /// custom contexts.
+    pub(crate) fn redact(&self, event: &mut Event<'static>) {
+        if self.redact_keys.is_empty() {
+            return;
+        }
+
+        let contexts = event
+            .contexts
+            .values_mut()
+            .filter_map(|context| match context {
+                Context::Other(map) => Some(map),
+                _ => None,
+            });
+
+        for map in [&mut event.extra].into_iter().chain(contexts) {
+            for (key, value) in map.iter_mut() {
+                self.redact_entry(key, value);
+            }
+        }
+    }
+
+    /// Trims the event until it fits in [`max_payload_bytes`](EventOptions::max_payload_bytes).
+    pub(crate) fn trim_payload(&self, event: &mut Event<'static>) {
+        if let Some(max) = self.max_payload_bytes {
+            trim_payload(event, max);
+        }
+    }
+
+    fn redact_entry(&self, key: &str, value: &mut Value) {
+        if self
+            .redact_keys
+            .iter()
+            .any(|redacted| redacted.eq_ignore_ascii_case(key))
+        {
+            *value = Value::String("[REDACTED]".into());
+            return;
+        }
+
+        self.redact_value(value);
+    }
+
+    fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    self.redact_entry(key, value);
+                }
+            }
+
+            Value::Array(values) => {
+                for value in values.iter_mut() {
+                    self.redact_value(value);
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+/// Expands to the name of the crate it's invoked in (as a `Cow<'static, str>`), to be used as
+/// the [`EventOptions::default_module`].
+#[macro_export]
+macro_rules! default_module {
+    () => {
+        ::std::borrow::Cow::Borrowed(::core::env!("CARGO_PKG_NAME"))
+    };
+}
+
+/// Sets the [`EventOptions`] that are used by every capture function that doesn't take
+/// explicit options, like [`capture_report`](crate::capture_report) and
+/// [`event_from_report`](crate::event_from_report). These can be temporarily overridden on
+/// the current thread with [`with_options`].
+pub fn set_default_options(options: EventOptions) {
+    *DEFAULT_OPTIONS.write().unwrap() = Some(Arc::new(options));
+}
+
+/// Runs the given closure with `options` overriding the [default options](set_default_options)
+/// on the current thread. Overrides can be nested, in which case the innermost one wins; the
+/// previous options are restored afterwards (even if the closure panics).
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, set_default_options, with_options, EventOptions};
+///
+/// set_default_options(EventOptions {
+///     dist: Some("global".into()),
+///     ..Default::default()
+/// });
+///
+/// let scoped = EventOptions {
+///     dist: Some("scoped".into()),
+///     ..Default::default()
+/// };
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     with_options(scoped.clone(), || {
+///         with_options(EventOptions::default(), || capture_report(&eyre::eyre!("innermost")));
+///         capture_report(&eyre::eyre!("scoped"));
+///     });
+///
+///     capture_report(&eyre::eyre!("global"));
+/// });
+///
+/// assert_eq!(events[0].dist, None);
+/// assert_eq!(events[1].dist.as_deref(), Some("scoped"));
+/// assert_eq!(events[2].dist.as_deref(), Some("global"));
+/// ```
+pub fn with_options<F: FnOnce() -> R, R>(options: EventOptions, f: F) -> R {
+    struct PopGuard;
+    impl Drop for PopGuard {
+        fn drop(&mut self) {
+            SCOPED_OPTIONS.with_borrow_mut(|stack| stack.pop());
+        }
+    }
+
+    SCOPED_OPTIONS.with_borrow_mut(|stack| stack.push(Arc::new(options)));
+    let _guard = PopGuard;
+    f()
+}
+
+/// Returns the effective options on the current thread: the innermost [`with_options`]
+/// override, the options of the active client's [`EyreIntegration`], or the
+/// [default options](set_default_options) otherwise.
+pub(crate) fn current_options() -> Arc<EventOptions> {
+    SCOPED_OPTIONS
+        .with_borrow(|stack| stack.last().cloned())
+        .or_else(|| {
+            Hub::with_active(|hub| {
+                hub.with_integration(|integration: &EyreIntegration| {
+                    Some(integration.options.clone())
+                })
+            })
+        })
+        .or_else(|| DEFAULT_OPTIONS.read().unwrap().clone())
+        .unwrap_or_default()
+}
+
+/// Trims the event until its serialized size is at most `max` bytes. Refer to
+/// [`EventOptions::max_payload_bytes`] on what is trimmed.
+pub(crate) fn trim_payload(event: &mut Event<'static>, max: usize) {
+    let size = |event: &Event<'static>| serde_json::to_vec(event).map_or(0, |buf| buf.len());
+    if size(event) <= max {
+        return;
+    }
+
+    let mut trimmed = Vec::new();
+    let mut note = |event: &mut Event<'static>, step: &str| {
+        trimmed.push(Value::from(step));
+        event
+            .extra
+            .insert("payload_trimmed".into(), Value::Array(trimmed.clone()));
+    };
+
+    event.extra.clear();
+    note(event, "extra");
+    if size(event) <= max {
+        return;
+    }
+
+    if !event.breadcrumbs.is_empty() {
+        event.breadcrumbs.values.clear();
+        note(event, "breadcrumbs");
+        if size(event) <= max {
+            return;
+        }
+    }
+
+    let values = event
+        .exception
+        .iter_mut()
+        .filter_map(|exc| exc.value.as_mut())
+        .chain(event.message.as_mut());
+
+    for value in values {
+        truncate(value, MAX_TRIMMED_VALUE_LEN);
+    }
+
+    note(event, "values");
+    if size(event) <= max {
+        return;
+    }
+
+    note(event, "frames");
+    loop {
+        let mut remaining = 0;
+        for frames in event
+            .exception
+            .iter_mut()
+            .filter_map(|exc| exc.stacktrace.as_mut())
+            .map(|stacktrace| &mut stacktrace.frames)
+        {
+            // Frames are ordered from the oldest to the most recent call, so the most
+            // relevant ones are at the end.
+            frames.drain(..frames.len().div_ceil(2));
+            remaining += frames.len();
+        }
+
+        if remaining == 0 || size(event) <= max {
+            return;
+        }
+    }
+}
+
+/// Truncates the given string to `max` characters, marking it with an ellipsis if it was.
+pub(crate) fn truncate(s: &mut String, max: usize) {
+    if let Some((idx, _)) = s.char_indices().nth(max) {
+        s.truncate(idx);
+        s.push('…');
+    }
+}
+
+/// Strips ANSI escape sequences off the given string and replaces all other control
+/// characters (except for newlines and tabs) with `U+FFFD`.
+pub(crate) fn sanitize(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\x1b' => match chars.next() {
+                // CSI sequences (i.e. colors) end with a byte in the `@`..=`~` range.
+                Some('[') => {
+                    for ch in chars.by_ref() {
+                        if ('@'..='~').contains(&ch) {
+                            break;
+                        }
+                    }
+                }
+
+                // OSC sequences (i.e. hyperlinks) end with BEL or ST (`ESC \\`).
+                Some(']') => {
+                    while let Some(ch) = chars.next() {
+                        if ch == '\x07' || (ch == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+
+                // Every other escape sequence is followed by a single character.
+                _ => {}
+            },
+
+            '\n' | '\t' => out.push(ch),
+            ch if ch.is_control() => out.push(char::REPLACEMENT_CHARACTER),
+            ch => out.push(ch),
+        }
+    }
+
+    out
+}
+
+/// Type name transform for [`EventOptions::type_name_transform`] that strips module paths
+/// off every path in the type name, i.e. `alloc::boxed::Box<dyn core::error::Error>` becomes
+/// `Box<dyn Error>`.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{event_from_report_with_options, strip_module_path, EventOptions};
+/// use std::{error::Error, fmt, sync::Arc};
+///
+/// assert_eq!(strip_module_path("std::io::error::Error"), "Error");
+/// assert_eq!(strip_module_path("alloc::boxed::Box<dyn core::error::Error>"), "Box<dyn Error>");
+///
+/// struct InvoiceError;
+///
+/// impl fmt::Debug for InvoiceError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("billing::invoice::InvoiceError")
+///     }
+/// }
+///
+/// impl fmt::Display for InvoiceError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("invoice is overdue")
+///     }
+/// }
+///
+/// impl Error for InvoiceError {}
+///
+/// let options = EventOptions {
+///     type_name_transform: Some(Arc::new(strip_module_path)),
+///     ..Default::default()
+/// };
+///
+/// let report = eyre::Report::new(InvoiceError);
+/// let event = event_from_report_with_options(&report, &EventOptions::default());
+/// assert_eq!(event.exception[0].ty, "billing::invoice::InvoiceError");
+///
+/// let event = event_from_report_with_options(&report, &options);
+/// assert_eq!(event.exception[0].ty, "InvoiceError");
+/// ```
+pub fn strip_module_path(ty: &str) -> String {
+    let mut out = String::with_capacity(ty.len());
+    let mut segment_start = 0;
+    let mut chars = ty.chars().peekable();
+    let mut in_segment = false;
+
+    while let Some(ch) = chars.next() {
+        if ch == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            out.truncate(segment_start);
+            in_segment = false;
+
+            continue;
+        }
+
+        let is_ident = ch.is_alphanumeric() || ch == '_';
+        if is_ident && !in_segment {
+            segment_start = out.len();
+        }
+
+        in_segment = is_ident;
+        out.push(ch);
+    }
+
+    out
+}
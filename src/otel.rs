@@ -0,0 +1,89 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture};
+use eyre::Report;
+use opentelemetry::{trace::TraceContextExt, Context};
+use sentry_core::{protocol::TraceContext, types::Uuid, Hub};
+
+/// Captures a [`Report`] with the trace context of the current [OpenTelemetry](https://docs.rs/opentelemetry)
+/// span, so the event can be correlated with the traces that are exported via OpenTelemetry rather
+/// than Sentry's own. If there is no valid span context, the event gets Sentry's trace context
+/// like it would with [`capture_report`](crate::capture_report).
+///
+/// ## Example
+/// ```
+/// use opentelemetry::{
+///     trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+///     Context,
+/// };
+/// use sentry::protocol::Context as SentryContext;
+/// use sentry_eyre::capture_report_otel;
+///
+/// let trace_id = TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap();
+/// let span_id = SpanId::from_hex("00f067aa0ba902b7").unwrap();
+/// let span_context = SpanContext::new(
+///     trace_id,
+///     span_id,
+///     TraceFlags::SAMPLED,
+///     true,
+///     TraceState::default(),
+/// );
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let _guard = Context::current()
+///         .with_remote_span_context(span_context)
+///         .attach();
+///
+///     capture_report_otel(&eyre::eyre!("upstream timed out"));
+/// });
+///
+/// let Some(SentryContext::Trace(trace)) = events[0].contexts.get("trace") else {
+///     panic!("expected a trace context");
+/// };
+///
+/// assert_eq!(trace.trace_id.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+/// assert_eq!(trace.span_id.to_string(), "00f067aa0ba902b7");
+/// ```
+pub fn capture_report_otel(report: &Report) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        let cx = Context::current();
+        let span_context = cx.span().span_context().clone();
+
+        // The scope only fills in its own trace context if the event doesn't have one yet.
+        if span_context.is_valid() {
+            let context = TraceContext {
+                trace_id: span_context.trace_id().to_bytes().into(),
+                span_id: span_context.span_id().to_bytes().into(),
+                ..Default::default()
+            };
+
+            event.contexts.insert("trace".into(), context.into());
+        }
+
+        hooks::capture_event(hub, report, event)
+    })
+}
@@ -0,0 +1,171 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Bridges panics into [`Report`]s, so that they're captured through the same pipeline as
+//! returned errors: they share fingerprints, mechanism metadata, and the registered
+//! [`ReportMapper`](crate::ReportMapper)s and hooks.
+
+use crate::{event_from_report, hooks, should_capture};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Event, Level},
+    types::Uuid,
+    Hub,
+};
+use std::{error::Error, fmt, panic::PanicHookInfo};
+
+/// The error that represents a panic within a [`Report`] created by [`report_from_panic`].
+#[derive(Debug)]
+pub struct PanicError {
+    message: String,
+    location: Option<String>,
+}
+
+impl PanicError {
+    /// Returns the message of the panic.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the location (`file:line:column`) at which the panic occurred, if known.
+    pub fn location(&self) -> Option<&str> {
+        self.location.as_deref()
+    }
+}
+
+impl fmt::Display for PanicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for PanicError {}
+
+/// Converts the payload of a panic into a [`Report`] holding a [`PanicError`], which carries
+/// the panic's message and location. The report captures a backtrace like any other report
+/// does (i.e. depending on `RUST_BACKTRACE` and the installed handler).
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::panic::{report_from_panic, PanicError};
+/// use std::sync::{Arc, Mutex};
+///
+/// let report = Arc::new(Mutex::new(None));
+/// let hook_report = report.clone();
+/// std::panic::set_hook(Box::new(move |info| {
+///     *hook_report.lock().unwrap() = Some(report_from_panic(info));
+/// }));
+///
+/// let _ = std::panic::catch_unwind(|| panic!("index out of range"));
+/// let _ = std::panic::take_hook();
+///
+/// let report = report.lock().unwrap().take().unwrap();
+/// let panic = report.downcast_ref::<PanicError>().unwrap();
+///
+/// assert_eq!(panic.message(), "index out of range");
+/// assert!(panic.location().unwrap().contains(".rs:"));
+/// ```
+pub fn report_from_panic(info: &PanicHookInfo<'_>) -> Report {
+    let payload = info.payload();
+    let message = match payload.downcast_ref::<&str>() {
+        Some(message) => (*message).to_owned(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "Box<dyn Any>".to_owned(),
+        },
+    };
+
+    Report::new(PanicError {
+        message,
+        location: info.location().map(|location| location.to_string()),
+    })
+}
+
+/// Utility function to represent a Sentry [`Event`] from a [`Report`] that was created by
+/// [`report_from_panic`]. The event is created with [`event_from_report`], with the outermost
+/// exception's mechanism marked as an unhandled `panic` that holds the panic's location as
+/// `location`, and the [`Level::Fatal`] level.
+pub fn event_from_panic_report(report: &Report) -> Event<'static> {
+    let mut event = event_from_report(report);
+    event.level = Level::Fatal;
+
+    let location = report
+        .downcast_ref::<PanicError>()
+        .and_then(PanicError::location);
+
+    if let Some(mechanism) = event
+        .exception
+        .last_mut()
+        .and_then(|exc| exc.mechanism.as_mut())
+    {
+        mechanism.ty = "panic".into();
+        mechanism.handled = Some(false);
+        if let Some(location) = location {
+            mechanism.data.insert("location".into(), location.into());
+        }
+    }
+
+    event
+}
+
+/// Captures the given panic as a [`Report`] (see [`report_from_panic`]) with the current
+/// [`Hub`], going through the same pipeline as [`capture_report`](crate::capture_report).
+pub fn capture_panic(info: &PanicHookInfo<'_>) -> Uuid {
+    Hub::with_active(|hub| {
+        let report = report_from_panic(info);
+        if !should_capture(hub, &report) {
+            return Uuid::nil();
+        }
+
+        hooks::capture_event(hub, &report, event_from_panic_report(&report))
+    })
+}
+
+/// Installs a panic hook that captures panics with [`capture_panic`], before calling the
+/// previously installed hook (i.e. the one that prints the panic message).
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::Level;
+///
+/// sentry_eyre::panic::install_panic_hook();
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let _ = std::panic::catch_unwind(|| panic!("index out of range"));
+/// });
+///
+/// let exc = &events[0].exception[0];
+/// let mechanism = exc.mechanism.as_ref().unwrap();
+///
+/// assert_eq!(events[0].level, Level::Fatal);
+/// assert_eq!(exc.ty, "PanicError");
+/// assert_eq!(exc.value.as_deref(), Some("index out of range"));
+/// assert_eq!(mechanism.ty, "panic");
+/// assert_eq!(mechanism.handled, Some(false));
+/// assert!(mechanism.data.contains_key("location"));
+/// ```
+pub fn install_panic_hook() {
+    let next = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        capture_panic(info);
+        next(info);
+    }));
+}
@@ -0,0 +1,154 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Event, SpanStatus},
+    types::Uuid,
+    Hub, Transaction,
+};
+use std::error::Error;
+
+/// Captures a [`Report`] and links it to the active transaction or span of the current
+/// scope, so the trace shows where it failed. If there is no active span, this behaves
+/// like [`capture_report`](crate::capture_report).
+///
+/// ## Example
+/// ```
+/// use sentry::{protocol::Context, ClientOptions, TransactionContext};
+/// use sentry_eyre::capture_report_on_span;
+///
+/// let mut trace_id = None;
+/// let events = sentry::test::with_captured_events_options(
+///     || {
+///         let txn = sentry::start_transaction(TransactionContext::new("job", "task"));
+///         trace_id = Some(txn.get_trace_context().trace_id);
+///
+///         sentry::configure_scope(|scope| scope.set_span(Some(txn.into())));
+///         capture_report_on_span(&eyre::eyre!("job failed"));
+///     },
+///     ClientOptions {
+///         traces_sample_rate: 1.0,
+///         ..Default::default()
+///     },
+/// );
+///
+/// let Some(Context::Trace(trace)) = events[0].contexts.get("trace") else {
+///     panic!("expected a trace context");
+/// };
+///
+/// assert_eq!(Some(trace.trace_id), trace_id);
+/// ```
+pub fn capture_report_on_span(report: &Report) -> Uuid {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        if let Some(span) = hub.configure_scope(|scope| scope.get_span()) {
+            event
+                .contexts
+                .insert("trace".into(), span.get_trace_context().into());
+        }
+
+        hooks::capture_event(hub, report, event)
+    })
+}
+
+/// Marks the given transaction as failed with the [`SpanStatus::InternalError`] status,
+/// captures the [`Report`] linked to its trace, and finishes the transaction.
+///
+/// The transaction is finished even if the report itself isn't captured (i.e. when it is
+/// suppressed), so that the performance data is never lost.
+///
+/// ## Example
+/// ```
+/// use sentry::{
+///     protocol::{Context, EnvelopeItem, SpanStatus},
+///     ClientOptions, TransactionContext,
+/// };
+/// use sentry_eyre::capture_report_fail_transaction;
+///
+/// let envelopes = sentry::test::with_captured_envelopes_options(
+///     || {
+///         let txn = sentry::start_transaction(TransactionContext::new("job", "task"));
+///         capture_report_fail_transaction(&eyre::eyre!("job failed"), &txn);
+///     },
+///     ClientOptions {
+///         traces_sample_rate: 1.0,
+///         ..Default::default()
+///     },
+/// );
+///
+/// let mut items = envelopes.iter().flat_map(|envelope| envelope.items());
+/// let Some(EnvelopeItem::Event(event)) = items.next() else {
+///     panic!("expected an event");
+/// };
+///
+/// let Some(EnvelopeItem::Transaction(txn)) = items.next() else {
+///     panic!("expected a transaction");
+/// };
+///
+/// let (Some(Context::Trace(event_trace)), Some(Context::Trace(txn_trace))) =
+///     (event.contexts.get("trace"), txn.contexts.get("trace"))
+/// else {
+///     panic!("expected trace contexts");
+/// };
+///
+/// assert_eq!(event_trace.trace_id, txn_trace.trace_id);
+/// assert_eq!(txn_trace.status, Some(SpanStatus::InternalError));
+/// ```
+pub fn capture_report_fail_transaction(report: &Report, txn: &Transaction) -> Uuid {
+    txn.set_status(SpanStatus::InternalError);
+
+    let event_id = Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Uuid::nil();
+        }
+
+        let mut event = event_from_report(report);
+        event
+            .contexts
+            .insert("trace".into(), txn.get_trace_context().into());
+
+        hooks::capture_event(hub, report, event)
+    });
+
+    txn.clone().finish();
+    event_id
+}
+
+/// Marks the active transaction or span of the current scope as failed, recording the
+/// (outermost) error's message on it, and links the event to it. See
+/// [`EyreIntegration::fail_active_span`](crate::EyreIntegration::fail_active_span).
+pub(crate) fn fail_active_span(hub: &Hub, err: &dyn Error, event: &mut Event<'static>) {
+    let Some(span) = hub.configure_scope(|scope| scope.get_span()) else {
+        return;
+    };
+
+    span.set_status(SpanStatus::InternalError);
+    span.set_data("error.message", err.to_string().into());
+    event
+        .contexts
+        .insert("trace".into(), span.get_trace_context().into());
+}
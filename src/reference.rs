@@ -0,0 +1,93 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::capture_report;
+use eyre::Report;
+use sentry_core::types::Uuid;
+use std::fmt;
+
+/// The context that [`capture_and_tag`] wraps a captured report with.
+struct EventReference(Uuid);
+
+impl fmt::Display for EventReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error reference: {}", self.0.simple())
+    }
+}
+
+impl fmt::Debug for EventReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Captures a [`Report`] and wraps it with a context that holds the id of the captured event,
+/// so it can be shown to users (i.e. in an HTTP response) to reference the event later on.
+/// The id can be retrieved with [`report_event_id`], and the context renders as
+/// `error reference: <event id>`.
+///
+/// The report is returned as-is if it wasn't captured. Capturing the returned report again
+/// creates a new event, use [`capture_report_once`](crate::capture_report_once) if it might
+/// flow through several layers that capture it.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_and_tag, report_event_id};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = capture_and_tag(eyre::eyre!("payment declined"));
+///     let event_id = report_event_id(&report).unwrap();
+///
+///     assert_eq!(report.to_string(), format!("error reference: {}", event_id.simple()));
+///     assert_eq!(report.root_cause().to_string(), "payment declined");
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn capture_and_tag(report: Report) -> Report {
+    let event_id = capture_report(&report);
+    if event_id.is_nil() {
+        return report;
+    }
+
+    report.wrap_err(EventReference(event_id))
+}
+
+/// Returns the id of the event that the [`Report`] was captured as by [`capture_and_tag`], if
+/// any. The report can be wrapped with further contexts in the meantime.
+///
+/// ## Example
+/// ```
+/// use eyre::WrapErr;
+/// use sentry_eyre::{capture_and_tag, report_event_id};
+///
+/// sentry::test::with_captured_events(|| {
+///     let report = capture_and_tag(eyre::eyre!("payment declined")).wrap_err("checkout failed");
+///     assert!(report_event_id(&report).is_some());
+///
+///     assert_eq!(report_event_id(&eyre::eyre!("payment declined")), None);
+/// });
+/// ```
+pub fn report_event_id(report: &Report) -> Option<Uuid> {
+    report
+        .downcast_ref::<EventReference>()
+        .map(|reference| reference.0)
+}
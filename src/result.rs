@@ -0,0 +1,138 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::capture_report;
+use eyre::Report;
+use sentry_core::types::Uuid;
+
+/// Extension trait to capture the error of a `Result<T, Report>` in place.
+pub trait ResultExt<T>: crate::private::Sealed {
+    /// Returns the value on success, or captures the error with [`capture_report`] and
+    /// returns [`None`]. This is meant for fire-and-forget paths (like best-effort cleanup)
+    /// where the error isn't propagated.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::ResultExt;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     assert_eq!(Ok::<_, eyre::Report>(1).capture_and_ok(), Some(1));
+    ///     assert_eq!(Err::<i32, _>(eyre::eyre!("cleanup failed")).capture_and_ok(), None);
+    /// });
+    ///
+    /// assert_eq!(events.len(), 1);
+    /// ```
+    fn capture_and_ok(self) -> Option<T>;
+
+    /// Captures the error (if any) with [`capture_report`] and returns the result as-is, so
+    /// that it can still be propagated with `?`.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::ResultExt;
+    ///
+    /// fn sync() -> eyre::Result<()> {
+    ///     Err(eyre::eyre!("sync failed"))
+    /// }
+    ///
+    /// fn run() -> eyre::Result<()> {
+    ///     sync().capture()?;
+    ///     Ok(())
+    /// }
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     assert!(run().is_err());
+    /// });
+    ///
+    /// assert_eq!(events[0].exception[0].value.as_deref(), Some("sync failed"));
+    /// ```
+    fn capture(self) -> Self;
+
+    /// Same as [`capture`](ResultExt::capture), but also returns the id of the captured event,
+    /// which is [`None`] if the result was `Ok`.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::ResultExt;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let (result, event_id) = Ok::<_, eyre::Report>(1).capture_with_id();
+    ///     assert_eq!((result.unwrap(), event_id), (1, None));
+    ///
+    ///     let (result, event_id) = Err::<i32, _>(eyre::eyre!("sync failed")).capture_with_id();
+    ///     assert!(result.is_err());
+    ///     assert!(!event_id.unwrap().is_nil());
+    /// });
+    ///
+    /// assert_eq!(events.len(), 1);
+    /// ```
+    fn capture_with_id(self) -> (Self, Option<Uuid>)
+    where
+        Self: Sized;
+
+    /// Same as [`capture`](ResultExt::capture), but logs the error with [`log::error!`] if it
+    /// wasn't captured (i.e. because no client is bound), so that it isn't lost.
+    ///
+    /// **Feature:** `log` (*disabled by default*)
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::ResultExt;
+    ///
+    /// // No client is bound here, so the error is logged instead.
+    /// let result = Err::<(), _>(eyre::eyre!("sync failed")).capture_or_log();
+    /// assert!(result.is_err());
+    /// ```
+    #[cfg(feature = "log")]
+    fn capture_or_log(self) -> Self;
+}
+
+impl<T> ResultExt<T> for Result<T, Report> {
+    fn capture_and_ok(self) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(report) => {
+                capture_report(&report);
+                None
+            }
+        }
+    }
+
+    fn capture(self) -> Self {
+        self.capture_with_id().0
+    }
+
+    fn capture_with_id(self) -> (Self, Option<Uuid>) {
+        let event_id = self.as_ref().err().map(capture_report);
+        (self, event_id)
+    }
+
+    #[cfg(feature = "log")]
+    fn capture_or_log(self) -> Self {
+        if let Err(report) = &self {
+            if capture_report(report).is_nil() {
+                log::error!("{report:?}");
+            }
+        }
+
+        self
+    }
+}
@@ -0,0 +1,237 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, fingerprint::hash_report, hooks, should_capture};
+use eyre::Report;
+use sentry_core::{types::Uuid, Hub};
+
+/// The rate that [`capture_report_trace_aware`] applies to reports that are captured within
+/// a transaction or span that wasn't sampled.
+pub const UNSAMPLED_TRACE_RATE: f32 = 0.1;
+
+/// Captures a [`Report`] with respect to the sampling decision of the active transaction or
+/// span. Reports within a sampled trace (or outside of any trace) are always captured, while
+/// reports within an unsampled trace are captured at [`UNSAMPLED_TRACE_RATE`].
+///
+/// Returns [`None`] if the report was dropped (including by a hook or the client's
+/// `before_send`) or no client is bound.
+pub fn capture_report_trace_aware(report: &Report) -> Option<Uuid> {
+    capture_report_trace_aware_with_rate(report, UNSAMPLED_TRACE_RATE)
+}
+
+/// Same as [`capture_report_trace_aware`], but reports within an unsampled trace are
+/// captured at the given `unsampled_rate` instead.
+///
+/// ## Example
+/// ```
+/// use sentry::{ClientOptions, TransactionContext};
+/// use sentry_eyre::capture_report_trace_aware_with_rate;
+/// use std::sync::Arc;
+///
+/// for (traces_sample_rate, captured) in [(1.0, true), (0.0, false)] {
+///     let events = sentry::test::with_captured_events_options(
+///         || {
+///             let txn = sentry::start_transaction(TransactionContext::new("job", "task"));
+///             sentry::configure_scope(|scope| scope.set_span(Some(txn.clone().into())));
+///
+///             let report = eyre::eyre!("job failed");
+///             let id = capture_report_trace_aware_with_rate(&report, 0.0);
+///             assert_eq!(id.is_some(), captured);
+///         },
+///         ClientOptions {
+///             traces_sample_rate,
+///             ..Default::default()
+///         },
+///     );
+///
+///     assert_eq!(events.len(), captured as usize);
+/// }
+///
+/// let options = ClientOptions {
+///     before_send: Some(Arc::new(|_| None)),
+///     ..Default::default()
+/// };
+///
+/// sentry::test::with_captured_events_options(
+///     || assert!(capture_report_trace_aware_with_rate(&eyre::eyre!("job failed"), 1.0).is_none()),
+///     options,
+/// );
+/// ```
+pub fn capture_report_trace_aware_with_rate(report: &Report, unsampled_rate: f32) -> Option<Uuid> {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return None;
+        }
+
+        let sampled = hub
+            .configure_scope(|scope| scope.get_span())
+            .map(|span| span.is_sampled())
+            .unwrap_or(true);
+
+        if !sampled {
+            let client = hub.client()?;
+            if !client.sample_should_send(unsampled_rate) {
+                return None;
+            }
+        }
+
+        captured(hooks::capture_event(hub, report, event_from_report(report)))
+    })
+}
+
+/// Captures a [`Report`] with the given probability (clamped to `0.0..=1.0`), i.e. to keep
+/// only a fraction of a known noisy error. The decision is random for every capture, use a
+/// [`ConsistentSampler`] to make the same decision for every capture of the same report
+/// instead (i.e. across replicas).
+///
+/// Returns [`None`] if the report was dropped (including by a hook or the client's
+/// `before_send`) or no client is bound.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_sampled;
+/// use std::sync::Arc;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     for _ in 0..1000 {
+///         capture_report_sampled(&eyre::eyre!("cache miss"), 0.1);
+///     }
+/// });
+///
+/// assert!((50..150).contains(&events.len()));
+///
+/// let options = sentry::ClientOptions {
+///     before_send: Some(Arc::new(|_| None)),
+///     ..Default::default()
+/// };
+///
+/// sentry::test::with_captured_events_options(
+///     || assert!(capture_report_sampled(&eyre::eyre!("cache miss"), 1.0).is_none()),
+///     options,
+/// );
+/// ```
+pub fn capture_report_sampled(report: &Report, rate: f32) -> Option<Uuid> {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return None;
+        }
+
+        if !hub.client()?.sample_should_send(rate.clamp(0.0, 1.0)) {
+            return None;
+        }
+
+        captured(hooks::capture_event(hub, report, event_from_report(report)))
+    })
+}
+
+/// A sampler that makes the same decision for every capture of the same report, rather than
+/// a random one. The decision is derived from a stable hash of the report's chain (the same
+/// one [`DedupStore`](crate::DedupStore) uses), so a given error is either always or never
+/// sent, even across process restarts.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::ConsistentSampler;
+///
+/// let sampler = ConsistentSampler::new(0.5);
+/// let events = sentry::test::with_captured_events(|| {
+///     let first = sampler.capture_report(&eyre::eyre!("cache miss")).is_some();
+///     for _ in 0..10 {
+///         let sampled = sampler.capture_report(&eyre::eyre!("cache miss")).is_some();
+///         assert_eq!(sampled, first);
+///     }
+/// });
+///
+/// assert!(events.is_empty() || events.len() == 11);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ConsistentSampler {
+    rate: f32,
+}
+
+impl ConsistentSampler {
+    /// Creates a new [`ConsistentSampler`] that samples the given fraction of distinct
+    /// reports, clamped to `0.0..=1.0`.
+    pub fn new(rate: f32) -> Self {
+        ConsistentSampler {
+            rate: rate.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Returns whether the given [`Report`] is sampled, which is the same for every report
+    /// with the same chain.
+    pub fn is_sampled(&self, report: &Report) -> bool {
+        is_hash_sampled(hash_report(report), self.rate)
+    }
+
+    /// Captures a [`Report`] if it's sampled. Returns [`None`] if the report was dropped
+    /// (including by a hook or the client's `before_send`) or no client is bound.
+    ///
+    /// ## Example
+    /// ```
+    /// use sentry_eyre::ConsistentSampler;
+    /// use std::sync::Arc;
+    ///
+    /// let events = sentry::test::with_captured_events(|| {
+    ///     let report = eyre::eyre!("connection reset");
+    ///     assert!(ConsistentSampler::new(1.0).capture_report(&report).is_some());
+    ///     assert!(ConsistentSampler::new(0.0).capture_report(&report).is_none());
+    /// });
+    ///
+    /// assert_eq!(events.len(), 1);
+    ///
+    /// let options = sentry::ClientOptions {
+    ///     before_send: Some(Arc::new(|_| None)),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// sentry::test::with_captured_events_options(
+    ///     || {
+    ///         let report = eyre::eyre!("connection reset");
+    ///         assert!(ConsistentSampler::new(1.0).capture_report(&report).is_none());
+    ///     },
+    ///     options,
+    /// );
+    /// ```
+    pub fn capture_report(&self, report: &Report) -> Option<Uuid> {
+        Hub::with_active(|hub| {
+            if !should_capture(hub, report) || !self.is_sampled(report) {
+                return None;
+            }
+
+            captured(hooks::capture_event(hub, report, event_from_report(report)))
+        })
+    }
+}
+
+/// Checks if a report with the given [stable hash](crate::fingerprint::hash_report) is
+/// sampled at the given rate.
+pub(crate) fn is_hash_sampled(hash: u64, rate: f32) -> bool {
+    // Maps the hash onto `0.0..=1.0`, so that it can be compared with the rate.
+    let position = hash as f64 / u64::MAX as f64;
+    rate >= 1.0 || position < f64::from(rate)
+}
+
+/// Maps the id of a captured event to [`None`] if it's nil, i.e. because a hook or the
+/// client's `before_send` dropped the event.
+pub(crate) fn captured(event_id: Uuid) -> Option<Uuid> {
+    (!event_id.is_nil()).then_some(event_id)
+}
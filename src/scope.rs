@@ -0,0 +1,144 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture, CaptureReportExt};
+use eyre::Report;
+use sentry_core::{types::Uuid, Hub, Scope};
+use std::sync::Arc;
+
+/// Captures a [`Report`] within a temporary scope that is configured by `f`, i.e. to tag
+/// it with the request's id or user. The scope is popped right after the report was captured,
+/// so the changes don't leak into later events.
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::User;
+/// use sentry_eyre::capture_report_with_scope;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_with_scope(&eyre::eyre!("checkout failed"), |scope| {
+///         scope.set_tag("request_id", "4f2a");
+///         scope.set_user(Some(User {
+///             id: Some("42".into()),
+///             ..Default::default()
+///         }));
+///     });
+///
+///     sentry_eyre::capture_report(&eyre::eyre!("cart expired"));
+/// });
+///
+/// assert_eq!(events[0].tags["request_id"], "4f2a");
+/// assert_eq!(events[0].user.as_ref().unwrap().id.as_deref(), Some("42"));
+/// assert!(!events[1].tags.contains_key("request_id"));
+/// ```
+pub fn capture_report_with_scope<F>(report: &Report, f: F) -> Uuid
+where
+    F: FnOnce(&mut Scope),
+{
+    Hub::with_active(|hub| hub.capture_report_with_scope(report, f))
+}
+
+/// Captures a [`Report`] within a temporary scope that is configured by `f`. If `f` returns
+/// `false`, capturing is aborted (i.e. because a tenant opted out) and this returns [`None`].
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_with_scope_filtered;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::eyre!("billing failed");
+///     let id = capture_report_with_scope_filtered(&report, |scope| {
+///         scope.set_tag("tenant", "opted-out");
+///         false
+///     });
+///
+///     assert!(id.is_none());
+/// });
+///
+/// assert!(events.is_empty());
+/// ```
+pub fn capture_report_with_scope_filtered<F>(report: &Report, f: F) -> Option<Uuid>
+where
+    F: FnOnce(&mut Scope) -> bool,
+{
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return None;
+        }
+
+        let _guard = hub.push_scope();
+        if !hub.configure_scope(f) {
+            return None;
+        }
+
+        Some(hooks::capture_event(hub, report, event_from_report(report)))
+    })
+}
+
+/// Sets the transaction name of the current scope and captures a [`Report`], i.e. at the
+/// start of a request handler that failed. The transaction name stays set on the scope
+/// afterwards, so later events of the same scope are associated with it as well.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::capture_report_with_transaction;
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     capture_report_with_transaction(&eyre::eyre!("user not found"), "GET /users/:id");
+/// });
+///
+/// assert_eq!(events[0].transaction.as_deref(), Some("GET /users/:id"));
+/// ```
+pub fn capture_report_with_transaction(report: &Report, transaction: &str) -> Uuid {
+    Hub::with_active(|hub| {
+        hub.configure_scope(|scope| scope.set_transaction(Some(transaction)));
+        hub.capture_report(report)
+    })
+}
+
+/// Runs `f` with a new [`Hub`] that is cloned from the current one and bound to the current
+/// thread while `f` runs, i.e. so that every plugin of a plugin system reports under its own
+/// isolated scope. Changes to the scope within `f` don't leak into the current hub, which
+/// is restored afterwards.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{with_isolated_hub, CaptureReportExt};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     with_isolated_hub(|hub| {
+///         hub.configure_scope(|scope| scope.set_tag("plugin", "thumbnails"));
+///         hub.capture_report(&eyre::eyre!("failed to decode image"));
+///     });
+///
+///     sentry_eyre::capture_report(&eyre::eyre!("failed to save upload"));
+/// });
+///
+/// assert_eq!(events[0].tags["plugin"], "thumbnails");
+/// assert!(!events[1].tags.contains_key("plugin"));
+/// ```
+pub fn with_isolated_hub<F, R>(f: F) -> R
+where
+    F: FnOnce(&Arc<Hub>) -> R,
+{
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    Hub::run(hub.clone(), || f(&hub))
+}
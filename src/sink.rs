@@ -0,0 +1,223 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, is_capture_allowed, mapper};
+use eyre::Report;
+use sentry_core::{
+    protocol::{Envelope, Event},
+    types::Uuid,
+    Hub,
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+/// The extension of the files that [`FileSink`] spools events to.
+const SPOOLED_EXTENSION: &str = "envelope";
+
+static SINK: RwLock<Option<(Arc<dyn ReportSink>, SinkMode)>> = RwLock::new(None);
+
+/// Stores the events of reports that [`capture_report`](crate::capture_report) couldn't send
+/// to Sentry, i.e. in tools that run without network access. Sinks are registered globally
+/// with [`set_report_sink`].
+///
+/// Only [`capture_report`](crate::capture_report) and
+/// [`CaptureReportExt::capture_report`](crate::CaptureReportExt::capture_report) store events
+/// with the sink; all other ways of capturing reports send them as usual.
+pub trait ReportSink: Send + Sync {
+    /// Stores the event that was created from a report.
+    fn store(&self, event: Event<'static>) -> io::Result<()>;
+}
+
+/// When the registered [`ReportSink`] is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SinkMode {
+    /// Events of [`capture_report`](crate::capture_report) are only stored when no enabled
+    /// client is bound, and sent otherwise.
+    #[default]
+    Fallback,
+
+    /// Events of [`capture_report`](crate::capture_report) are always stored instead of being
+    /// sent.
+    Always,
+}
+
+/// Registers the [`ReportSink`] that [`capture_report`](crate::capture_report) stores events
+/// with, replacing the previous one. Reports that are suppressed (i.e. by the kill switch or
+/// [`add_suppressed_type`](crate::add_suppressed_type)) or sampled out by a
+/// [`ReportMapper`](crate::ReportMapper) aren't stored either, and events are
+/// passed through the [`before_capture`](crate::add_before_capture) hooks before they're
+/// stored, so that they're scrubbed before they hit the disk.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{
+///     capture_report, flush_spooled, set_report_sink, FileSink, ReportMapper, SinkMode,
+/// };
+///
+/// let dir = std::env::temp_dir().join(format!("sentry-eyre-spool-{}", std::process::id()));
+/// set_report_sink(FileSink::new(&dir), SinkMode::Fallback);
+///
+/// sentry_eyre::add_before_capture(|_, mut event| {
+///     event.user = None;
+///     event.tags.insert("scrubbed".into(), "true".into());
+///     Some(event)
+/// });
+///
+/// // No client is bound, i.e. while offline.
+/// let event_id = capture_report(&eyre::eyre!("sync failed"));
+/// assert!(!event_id.is_nil());
+///
+/// // Sample rates apply to stored events as well.
+/// ReportMapper::for_type::<std::num::ParseIntError>().sample_rate(0.0).register();
+/// assert!(capture_report(&eyre::Report::new("x".parse::<u8>().unwrap_err())).is_nil());
+///
+/// // ...later on, once a client is bound again
+/// let events = sentry::test::with_captured_events(|| {
+///     assert_eq!(flush_spooled(&dir).unwrap(), 1);
+/// });
+///
+/// assert_eq!(events[0].event_id, event_id);
+/// assert_eq!(events[0].tags["scrubbed"], "true");
+/// assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+/// # std::fs::remove_dir(&dir).unwrap();
+/// ```
+pub fn set_report_sink<S: ReportSink + 'static>(sink: S, mode: SinkMode) {
+    *SINK.write().unwrap() = Some((Arc::new(sink), mode));
+}
+
+/// Removes the [`ReportSink`] that was registered with [`set_report_sink`].
+pub fn clear_report_sink() {
+    *SINK.write().unwrap() = None;
+}
+
+/// Stores the report's event with the registered [`ReportSink`] if it should be used for
+/// the given [`Hub`]. Returns [`None`] if the report should be captured as usual instead.
+pub(crate) fn spool_report(hub: &Hub, report: &Report) -> Option<Uuid> {
+    let (sink, mode) = SINK.read().unwrap().clone()?;
+    if mode == SinkMode::Fallback && hub.client().is_some_and(|client| client.is_enabled()) {
+        return None;
+    }
+
+    if !is_capture_allowed(report.as_ref()) || mapper::is_sampled_out(hub, report.as_ref()) {
+        return Some(Uuid::nil());
+    }
+
+    let Some(event) = hooks::run_hooks(hub, report.as_ref(), event_from_report(report)) else {
+        return Some(Uuid::nil());
+    };
+
+    let event_id = event.event_id;
+
+    Some(match sink.store(event) {
+        Ok(()) => event_id,
+        Err(_) => Uuid::nil(),
+    })
+}
+
+/// Stores the report's event with the registered [`ReportSink`] when the given [`Hub`] isn't
+/// active, returning a nil id if there's no sink or an enabled client is bound (i.e. while
+/// the hub is already capturing an event).
+pub(crate) fn spool_offline_report(hub: &Hub, report: &Report) -> Uuid {
+    if hub.client().is_some_and(|client| client.is_enabled()) {
+        return Uuid::nil();
+    }
+
+    spool_report(hub, report).unwrap_or_default()
+}
+
+/// A [`ReportSink`] that spools every event as an envelope file (named after the event's id)
+/// into a directory, which is created if it doesn't exist yet. Spooled events can be sent
+/// later on with [`flush_spooled`].
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    dir: PathBuf,
+}
+
+impl FileSink {
+    /// Creates a new [`FileSink`] that spools events into the given directory.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        FileSink {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl ReportSink for FileSink {
+    fn store(&self, event: Event<'static>) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self
+            .dir
+            .join(format!("{}.{SPOOLED_EXTENSION}", event.event_id.simple()));
+
+        let mut buf = Vec::new();
+        Envelope::from(event).to_writer(&mut buf)?;
+        fs::write(path, buf)
+    }
+}
+
+/// Sends the events that a [`FileSink`] spooled into the given directory through the current
+/// [`Hub`], removing their files. Returns how many events were sent, which is `0` if no
+/// enabled client is bound (in which case the files are kept).
+///
+/// The events were already passed through the hooks before they were spooled, so they're
+/// sent as-is. Files whose event isn't captured (i.e. because the client's `before_send`
+/// dropped it), that don't hold an event, or that can't be parsed as an envelope are kept.
+pub fn flush_spooled<P: AsRef<Path>>(dir: P) -> io::Result<usize> {
+    let hub = Hub::current();
+    if !hub.client().is_some_and(|client| client.is_enabled()) {
+        return Ok(0);
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut sent = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != SPOOLED_EXTENSION) {
+            continue;
+        }
+
+        let Ok(envelope) = Envelope::from_path(&path) else {
+            continue;
+        };
+
+        let Some(event) = envelope.event() else {
+            continue;
+        };
+
+        if hub.capture_event(event.clone()).is_nil() {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
@@ -0,0 +1,77 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::fingerprint::is_probably_in_app;
+use sentry_core::protocol::{Event, Frame};
+use std::{collections::HashMap, fs};
+
+/// The maximum amount of frames of an event that are enriched with source context, to
+/// bound the I/O done per event.
+const MAX_SOURCE_CONTEXT_FRAMES: usize = 10;
+
+/// The amount of lines that are attached before and after the frame's line.
+const CONTEXT_LINES: usize = 5;
+
+/// Attaches the lines around the frame's line as `pre_context`, `context_line` and
+/// `post_context` to the most recent in-app frames of the event whose source file can be
+/// read at runtime.
+pub(crate) fn attach_source_context(event: &mut Event<'static>) {
+    // Files are only read once, even if several frames point into them.
+    let mut files = HashMap::<String, Option<Vec<String>>>::new();
+    let frames = event
+        .exception
+        .iter_mut()
+        .rev()
+        .filter_map(|exc| exc.stacktrace.as_mut())
+        .flat_map(|stacktrace| stacktrace.frames.iter_mut().rev())
+        .filter(|frame| match frame.in_app {
+            Some(in_app) => in_app,
+            None => is_probably_in_app(frame),
+        })
+        .take(MAX_SOURCE_CONTEXT_FRAMES);
+
+    for frame in frames {
+        let (Some(path), Some(lineno)) = (frame.abs_path.clone(), frame.lineno) else {
+            continue;
+        };
+
+        let lines = files.entry(path).or_insert_with_key(|path| {
+            fs::read_to_string(path)
+                .ok()
+                .map(|source| source.lines().map(String::from).collect())
+        });
+
+        if let Some(lines) = lines {
+            fill_context(frame, lines, lineno as usize);
+        }
+    }
+}
+
+fn fill_context(frame: &mut Frame, lines: &[String], lineno: usize) {
+    // Line numbers are 1-based.
+    let Some(idx) = lineno.checked_sub(1).filter(|idx| *idx < lines.len()) else {
+        return;
+    };
+
+    frame.pre_context = lines[idx.saturating_sub(CONTEXT_LINES)..idx].to_vec();
+    frame.context_line = Some(lines[idx].clone());
+    frame.post_context = lines[idx + 1..(idx + 1 + CONTEXT_LINES).min(lines.len())].to_vec();
+}
@@ -0,0 +1,151 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::EventOptions;
+use eyre::Report;
+use sentry_core::protocol::{Breadcrumb, Context, Event, Level, Map, Value};
+use tracing_error::SpanTrace;
+
+/// The key of the context that the spans of a [`SpanTrace`] are attached as.
+const SPANS_CONTEXT: &str = "trace_spans";
+
+/// Attaches the spans of a [`SpanTrace`] to the event, so it shows what the application was
+/// doing when the error occurred. Every span is attached as a breadcrumb in the `span`
+/// category (outermost span first), and as an entry of the `spans` list of the
+/// `trace_spans` context with its name, target, fields and source location.
+///
+/// Events that are created from reports get the `SpanTrace` attached automatically if it can
+/// be found, see [`span_trace_from_report`].
+///
+/// **Feature:** `tracing` or `color-eyre` (*disabled by default*)
+///
+/// ## Example
+/// ```
+/// use sentry::protocol::{Context, Event};
+/// use sentry_eyre::attach_span_trace;
+/// use tracing_error::{ErrorLayer, SpanTrace};
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry().with(ErrorLayer::default()).init();
+///
+/// let span_trace = tracing::info_span!("sync", tenant = "acme")
+///     .in_scope(|| tracing::info_span!("fetch_page", page = 2).in_scope(SpanTrace::capture));
+///
+/// let mut event = Event::new();
+/// attach_span_trace(&mut event, &span_trace);
+///
+/// let names = event
+///     .breadcrumbs
+///     .iter()
+///     .map(|breadcrumb| breadcrumb.message.as_deref().unwrap())
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(names, ["rust_out::sync", "rust_out::fetch_page"]);
+///
+/// let Context::Other(spans) = &event.contexts["trace_spans"] else {
+///     unreachable!();
+/// };
+///
+/// assert_eq!(spans["spans"][1]["name"], "fetch_page");
+/// assert_eq!(spans["spans"][1]["fields"], "page=2");
+/// ```
+pub fn attach_span_trace(event: &mut Event<'static>, span_trace: &SpanTrace) {
+    let mut breadcrumbs = Vec::new();
+    let mut spans = Vec::new();
+
+    span_trace.with_spans(|metadata, fields| {
+        let mut data = Map::new();
+        if !fields.is_empty() {
+            data.insert("fields".into(), fields.into());
+        }
+
+        if let Some(file) = metadata.file() {
+            data.insert("file".into(), file.into());
+        }
+
+        if let Some(line) = metadata.line() {
+            data.insert("line".into(), line.into());
+        }
+
+        let mut span = data.clone().into_iter().collect::<serde_json::Map<_, _>>();
+        span.insert("name".into(), metadata.name().into());
+        span.insert("target".into(), metadata.target().into());
+        spans.push(Value::Object(span));
+
+        breadcrumbs.push(Breadcrumb {
+            category: Some("span".into()),
+            message: Some(format!("{}::{}", metadata.target(), metadata.name())),
+            level: Level::Info,
+            data,
+            ..Default::default()
+        });
+
+        true
+    });
+
+    if spans.is_empty() {
+        return;
+    }
+
+    // Spans are visited from the innermost to the outermost one, while breadcrumbs are
+    // ordered chronologically.
+    breadcrumbs.reverse();
+    spans.reverse();
+
+    event.breadcrumbs.extend(breadcrumbs);
+    event.contexts.insert(
+        SPANS_CONTEXT.into(),
+        Context::Other(Map::from_iter([("spans".into(), spans.into())])),
+    );
+}
+
+/// Finds the [`SpanTrace`] of the report, which is looked up in the following order:
+///
+/// 1. the one captured by `color-eyre`'s handler (with the `color-eyre` feature),
+/// 2. the one extracted from the handler by [`EventOptions::extract_span_trace`],
+/// 3. the first one in the report's chain, i.e. from a [`TracedError`](tracing_error::TracedError).
+pub(crate) fn span_trace_from_report(report: &Report, options: &EventOptions) -> Option<SpanTrace> {
+    #[cfg(feature = "color-eyre")]
+    if let Some(span_trace) = crate::color::span_trace_from_report(report) {
+        return Some(span_trace.clone());
+    }
+
+    #[cfg(feature = "tracing")]
+    {
+        use tracing_error::ExtractSpanTrace;
+
+        if let Some(span_trace) = options
+            .extract_span_trace
+            .as_ref()
+            .and_then(|extract| extract(report.handler()))
+        {
+            return Some(span_trace);
+        }
+
+        report.chain().find_map(|err| err.span_trace()).cloned()
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = options;
+        None
+    }
+}
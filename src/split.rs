@@ -0,0 +1,84 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{event_from_report, hooks, should_capture};
+use eyre::Report;
+use sentry_core::{protocol::Event, types::Uuid, Hub};
+
+/// A splitter that can turn a single [`Report`] into multiple Sentry [`Event`]s. This
+/// is useful if one report logically represents several issues, i.e. if it aggregates
+/// sub-failures in its sections.
+pub trait ReportSplitter {
+    /// Splits the given [`Report`] into the events that should be sent to Sentry.
+    fn split(&self, report: &Report) -> Vec<Event<'static>>;
+}
+
+/// Default [`ReportSplitter`] which doesn't split at all and produces a single event
+/// via [`event_from_report`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultSplitter;
+
+impl ReportSplitter for DefaultSplitter {
+    fn split(&self, report: &Report) -> Vec<Event<'static>> {
+        vec![event_from_report(report)]
+    }
+}
+
+impl<F> ReportSplitter for F
+where
+    F: Fn(&Report) -> Vec<Event<'static>>,
+{
+    fn split(&self, report: &Report) -> Vec<Event<'static>> {
+        (self)(report)
+    }
+}
+
+/// Captures a [`Report`] by splitting it with the given [`ReportSplitter`] and sending
+/// each resulting event to Sentry separately.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_split, event_from_report};
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::eyre!("two things went wrong");
+///     let ids = capture_split(&report, &|report: &eyre::Report| {
+///         vec![event_from_report(report), event_from_report(report)]
+///     });
+///
+///     assert_eq!(ids.len(), 2);
+/// });
+///
+/// assert_eq!(events.len(), 2);
+/// ```
+pub fn capture_split<S: ReportSplitter + ?Sized>(report: &Report, splitter: &S) -> Vec<Uuid> {
+    Hub::with_active(|hub| {
+        if !should_capture(hub, report) {
+            return Vec::new();
+        }
+
+        splitter
+            .split(report)
+            .into_iter()
+            .map(|event| hooks::capture_event(hub, report, event))
+            .collect()
+    })
+}
@@ -0,0 +1,128 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use crate::{
+    event_from_report, hooks, is_capture_allowed, kill_switch::is_kill_switch_active, mapper,
+};
+use eyre::Report;
+use sentry_core::{types::Uuid, Hub};
+use std::{error::Error, fmt};
+
+/// Reason why [`capture_report_strict`] couldn't capture a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CaptureError {
+    /// No client is bound to the current [`Hub`], or the client is disabled (i.e. it has
+    /// no DSN).
+    NoClient,
+
+    /// Capturing is disabled by the [kill switch](crate::KILL_SWITCH_ENV).
+    Disabled,
+
+    /// The report contains an error of a [suppressed type](crate::add_suppressed_type), or of
+    /// a type that a [`ReportMapper`](crate::ReportMapper) suppresses.
+    Suppressed,
+
+    /// The event was dropped before it was sent, i.e. by a
+    /// [`before_capture`](crate::add_before_capture) hook, the client's `before_send`
+    /// callback or sampling (including the sample rate of a [`ReportMapper`](crate::ReportMapper)).
+    Dropped,
+}
+
+impl fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CaptureError::NoClient => "no enabled Sentry client is bound to the current hub",
+            CaptureError::Disabled => "capturing reports is disabled by the kill switch",
+            CaptureError::Suppressed => "report contains an error of a suppressed type",
+            CaptureError::Dropped => "event was dropped before it was sent",
+        })
+    }
+}
+
+impl Error for CaptureError {}
+
+/// Same as [`capture_report`](crate::capture_report), but returns why the report couldn't
+/// be captured instead of a nil [`Uuid`].
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{
+///     capture_report_strict, reload_kill_switch, CaptureError, ReportMapper, KILL_SWITCH_ENV,
+/// };
+/// use std::{error::Error, fmt};
+///
+/// #[derive(Debug)]
+/// struct TimedOut;
+///
+/// impl fmt::Display for TimedOut {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str("timed out")
+///     }
+/// }
+///
+/// impl Error for TimedOut {}
+///
+/// let report = eyre::eyre!("payment failed");
+/// assert_eq!(capture_report_strict(&report), Err(CaptureError::NoClient));
+///
+/// sentry::test::with_captured_events(|| {
+///     assert!(capture_report_strict(&report).is_ok());
+///
+///     ReportMapper::for_type::<TimedOut>().suppress().register();
+///     assert_eq!(
+///         capture_report_strict(&eyre::Report::new(TimedOut)),
+///         Err(CaptureError::Suppressed)
+///     );
+///
+///     std::env::set_var(KILL_SWITCH_ENV, "1");
+///     reload_kill_switch();
+///
+///     assert_eq!(capture_report_strict(&report), Err(CaptureError::Disabled));
+/// });
+/// ```
+pub fn capture_report_strict(report: &Report) -> Result<Uuid, CaptureError> {
+    if is_kill_switch_active() {
+        return Err(CaptureError::Disabled);
+    }
+
+    Hub::with(|hub| {
+        if !hub.client().is_some_and(|client| client.is_enabled()) {
+            return Err(CaptureError::NoClient);
+        }
+
+        // The kill switch was checked above already, so this only fails for suppressed types.
+        if !is_capture_allowed(report.as_ref()) {
+            return Err(CaptureError::Suppressed);
+        }
+
+        if mapper::is_sampled_out(hub, report.as_ref()) {
+            return Err(CaptureError::Dropped);
+        }
+
+        let event_id = hooks::capture_event(hub, report, event_from_report(report));
+        if event_id.is_nil() {
+            return Err(CaptureError::Dropped);
+        }
+
+        Ok(event_id)
+    })
+}
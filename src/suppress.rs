@@ -0,0 +1,69 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use std::{any::TypeId, error::Error, sync::RwLock};
+
+type Matcher = fn(&(dyn Error + 'static)) -> bool;
+
+static SUPPRESSED: RwLock<Vec<(TypeId, Matcher)>> = RwLock::new(Vec::new());
+
+/// Suppresses all reports that contain an error of type `T` anywhere in their chain. Capturing
+/// such a report is a no-op and returns a nil [`Uuid`](sentry_core::types::Uuid).
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{add_suppressed_type, capture_report};
+/// use std::io;
+///
+/// add_suppressed_type::<io::Error>();
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let report = eyre::Report::new(io::Error::from(io::ErrorKind::BrokenPipe));
+///     assert!(capture_report(&report).is_nil());
+/// });
+///
+/// assert!(events.is_empty());
+/// ```
+pub fn add_suppressed_type<T: Error + 'static>() {
+    let mut suppressed = SUPPRESSED.write().unwrap();
+    if suppressed.iter().all(|(id, _)| *id != TypeId::of::<T>()) {
+        suppressed.push((TypeId::of::<T>(), |err| err.is::<T>()));
+    }
+}
+
+/// Removes a type that was previously suppressed with [`add_suppressed_type`].
+pub fn remove_suppressed_type<T: Error + 'static>() {
+    SUPPRESSED
+        .write()
+        .unwrap()
+        .retain(|(id, _)| *id != TypeId::of::<T>());
+}
+
+/// Checks if any link in the error's chain is of a suppressed type.
+pub(crate) fn is_suppressed(err: &(dyn Error + 'static)) -> bool {
+    let suppressed = SUPPRESSED.read().unwrap();
+    if suppressed.is_empty() {
+        return false;
+    }
+
+    std::iter::successors(Some(err), |&err| err.source())
+        .any(|err| suppressed.iter().any(|(_, matches)| matches(err)))
+}
@@ -0,0 +1,181 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Utilities for testing how reports are captured.
+//!
+//! **Feature:** `test-util` (*disabled by default*)
+
+use crate::capture_report;
+use eyre::Report;
+use sentry_core::{protocol::Event, test::with_captured_events};
+use std::{backtrace::Backtrace, error::Error, fmt};
+
+/// Runs `f` with a client that uses Sentry's test transport bound to the current hub, and
+/// returns all events that were captured while it ran, i.e. via [`capture_report`].
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{capture_report, test::with_captured_reports};
+///
+/// let events = with_captured_reports(|| {
+///     capture_report(&eyre::eyre!("oh no"));
+/// });
+///
+/// assert_eq!(events.len(), 1);
+/// ```
+pub fn with_captured_reports<F: FnOnce()>(f: F) -> Vec<Event<'static>> {
+    with_captured_events(f)
+}
+
+/// Asserts that the exceptions of the given event carry the given values, ordered from the
+/// outermost error of the chain to the innermost one (the order in which [`Report::chain`]
+/// yields them).
+///
+/// ## Panics
+/// Panics if the values of the exceptions differ from the given ones.
+///
+/// ## Example
+/// ```
+/// use eyre::WrapErr;
+/// use sentry_eyre::{capture_report, test::{assert_chain_values, with_captured_reports}};
+///
+/// let report = Err::<(), _>(eyre::eyre!("inner")).wrap_err("outer").unwrap_err();
+/// let events = with_captured_reports(|| {
+///     capture_report(&report);
+/// });
+///
+/// assert_chain_values(&events[0], ["outer", "inner"]);
+/// ```
+#[track_caller]
+pub fn assert_chain_values<I, S>(event: &Event<'_>, values: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let actual = event
+        .exception
+        .iter()
+        .rev()
+        .map(|exc| exc.value.as_deref().unwrap_or_default())
+        .collect::<Vec<_>>();
+
+    let expected = values.into_iter().collect::<Vec<_>>();
+    let expected = expected.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+
+    assert_eq!(actual, expected, "values of the exception chain differ");
+}
+
+/// Captures the given [`Report`] under Sentry's test transport and invokes `assert` with
+/// the captured event, which is returned afterwards.
+///
+/// ## Panics
+/// Panics if not exactly one event was captured.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::test::capture_and_assert;
+///
+/// capture_and_assert(&eyre::eyre!("oh no"), |event| {
+///     assert_eq!(event.exception[0].value.as_deref(), Some("oh no"));
+/// });
+/// ```
+pub fn capture_and_assert<F: FnOnce(&Event<'static>)>(
+    report: &Report,
+    assert: F,
+) -> Event<'static> {
+    let mut events = with_captured_events(|| {
+        capture_report(report);
+    });
+
+    assert_eq!(events.len(), 1, "expected exactly one event to be captured");
+
+    let event = events.remove(0);
+    assert(&event);
+
+    event
+}
+
+/// Creates a [`Report`] that carries a backtrace regardless of the environment (i.e. even
+/// if `RUST_BACKTRACE` isn't set) and of the installed handler, so that tests that depend on
+/// backtraces are deterministic.
+///
+/// The backtrace is captured by the report's error itself, which the crate recognizes when
+/// the event is created.
+///
+/// ## Example
+/// ```
+/// use sentry_eyre::{event_from_report, test::report_with_backtrace};
+///
+/// let event = event_from_report(&report_with_backtrace());
+/// let frames = &event.exception[0].stacktrace.as_ref().unwrap().frames;
+///
+/// assert!(!frames.is_empty());
+/// assert!(frames.iter().all(|frame| frame.in_app.is_some()));
+///
+/// let in_app = |frame: &sentry::protocol::Frame, prefix| {
+///     frame.function.as_deref().is_some_and(|func| func.starts_with(prefix))
+///         && frame.in_app == Some(true)
+/// };
+///
+/// assert!(frames.iter().any(|frame| in_app(frame, "rust_out::")));
+/// assert!(!frames.iter().any(|frame| in_app(frame, "std::")));
+///
+/// // the client's `in_app_exclude` prefixes win
+/// let options = sentry::ClientOptions {
+///     in_app_exclude: vec!["rust_out::"],
+///     ..Default::default()
+/// };
+///
+/// sentry::test::with_captured_events_options(
+///     || {
+///         let event = event_from_report(&report_with_backtrace());
+///         let frames = &event.exception[0].stacktrace.as_ref().unwrap().frames;
+///         assert!(!frames.iter().any(|frame| in_app(frame, "rust_out::")));
+///     },
+///     options,
+/// );
+/// ```
+pub fn report_with_backtrace() -> Report {
+    Report::new(BacktraceError {
+        backtrace: Backtrace::force_capture(),
+    })
+}
+
+/// The error of the reports created by [`report_with_backtrace`].
+#[derive(Debug)]
+pub(crate) struct BacktraceError {
+    backtrace: Backtrace,
+}
+
+impl BacktraceError {
+    /// Returns the backtrace that was captured when the error was created.
+    pub(crate) fn backtrace(&self) -> &Backtrace {
+        &self.backtrace
+    }
+}
+
+impl fmt::Display for BacktraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("report with a backtrace")
+    }
+}
+
+impl Error for BacktraceError {}
@@ -0,0 +1,166 @@
+// 🐻‍❄️👀 sentry-eyre: Sentry integration for `eyre`.
+// Copyright (c) 2023-2024 Noel Towa <cutie@floofy.dev>
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! A [`tower`](https://docs.rs/tower) layer that captures the [`Report`]s of fallible services.
+//!
+//! **Feature:** `tower` (*disabled by default*)
+
+use crate::{http::request_from_http, CaptureErr, FutureReportExt};
+use eyre::Report;
+use sentry_core::Hub;
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// A [`Layer`] that captures the [`Report`] that the wrapped service fails with, along with
+/// the request's method, URL and allowed headers as the event's request context.
+///
+/// Every request is handled with its own [`Hub`] (forked off the current one), so scope
+/// changes of the service don't leak into other requests, and reports that the service
+/// captures itself carry the request context as well.
+///
+/// ## Example
+/// ```
+/// use eyre::Report;
+/// use sentry_eyre::tower::SentryEyreLayer;
+/// use std::{future::{ready, Future, Ready}, pin::pin, task::{Context, Poll, Waker}};
+/// use tower_layer::Layer;
+/// use tower_service::Service;
+///
+/// struct Handler;
+///
+/// impl Service<http::Request<()>> for Handler {
+///     type Response = &'static str;
+///     type Error = Report;
+///     type Future = Ready<Result<&'static str, Report>>;
+///
+///     fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Report>> {
+///         Poll::Ready(Ok(()))
+///     }
+///
+///     fn call(&mut self, _: http::Request<()>) -> Self::Future {
+///         ready(Err(eyre::eyre!("user store is unavailable")))
+///     }
+/// }
+///
+/// let events = sentry::test::with_captured_events(|| {
+///     let mut service = SentryEyreLayer::new()
+///         .allowed_headers(["user-agent"])
+///         .layer(Handler);
+///
+///     let request = http::Request::get("https://example.com/users/42")
+///         .header("User-Agent", "curl/8.0")
+///         .header("Cookie", "session=hunter2")
+///         .body(())
+///         .unwrap();
+///
+///     let response = pin!(service.call(request));
+///     let poll = response.poll(&mut Context::from_waker(Waker::noop()));
+///     assert!(matches!(poll, Poll::Ready(Err(_))));
+/// });
+///
+/// let request = events[0].request.as_ref().unwrap();
+/// assert_eq!(request.method.as_deref(), Some("GET"));
+/// assert_eq!(request.url.as_ref().unwrap().path(), "/users/42");
+/// assert!(request.headers.contains_key("user-agent"));
+/// assert!(!request.headers.contains_key("cookie"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SentryEyreLayer {
+    allowed_headers: Arc<[String]>,
+}
+
+impl SentryEyreLayer {
+    /// Creates a new [`SentryEyreLayer`] that doesn't include any headers in the request
+    /// context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the headers (compared case-insensitively) that are included in the request
+    /// context. Refer to [`request_from_http`] on why headers have to be allowed explicitly.
+    pub fn allowed_headers<I, S>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl<S> Layer<S> for SentryEyreLayer {
+    type Service = SentryEyreService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SentryEyreService {
+            inner,
+            allowed_headers: self.allowed_headers.clone(),
+        }
+    }
+}
+
+/// The service that [`SentryEyreLayer`] wraps services with.
+#[derive(Debug, Clone)]
+pub struct SentryEyreService<S> {
+    inner: S,
+    allowed_headers: Arc<[String]>,
+}
+
+impl<S, B> Service<http::Request<B>> for SentryEyreService<S>
+where
+    S: Service<http::Request<B>, Error = Report>,
+{
+    type Response = S::Response;
+    type Error = Report;
+    type Future = CaptureErr<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let allowed_headers = self
+            .allowed_headers
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+
+        let context = request_from_http(&request, &allowed_headers);
+        let hub = Arc::new(Hub::new_from_top(Hub::current()));
+        hub.configure_scope(|scope| {
+            scope.add_event_processor(move |mut event| {
+                if event.request.is_none() {
+                    event.request = Some(context.clone());
+                }
+
+                Some(event)
+            });
+        });
+
+        // The hub is bound to the response future by `capture_err`, which makes it the active
+        // one whenever the future is polled.
+        Hub::run(hub, || self.inner.call(request).capture_err())
+    }
+}
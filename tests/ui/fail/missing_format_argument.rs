@@ -0,0 +1,3 @@
+fn main() {
+    sentry_eyre::report!("shard {} is unavailable after {}s", 3);
+}
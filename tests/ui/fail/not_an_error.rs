@@ -0,0 +1,5 @@
+struct Shard(u32);
+
+fn main() {
+    sentry_eyre::report!(Shard(3));
+}
@@ -0,0 +1,12 @@
+use sentry_eyre::report;
+use std::io;
+
+fn main() {
+    let shard = 3;
+
+    let _ = report!("cache miss");
+    let _ = report!("shard {} is unavailable", shard);
+    let _ = report!("shard {shard} is unavailable");
+    let _ = report!("shard {} is unavailable after {}s", shard, 30,);
+    let _ = report!(io::Error::other("connection reset"));
+}